@@ -0,0 +1,22 @@
+fn main() {
+    // The C header is only needed, and cbindgen only available, with the C API
+    #[cfg(feature = "ffi")]
+    generate_c_header();
+}
+
+#[cfg(feature = "ffi")]
+fn generate_c_header() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir)).expect("cbindgen.toml is valid");
+    // Only the C API's file is read, so nothing else in the crate ends up in
+    // the header
+    cbindgen::Builder::new()
+        .with_src(format!("{}/src/ffi.rs", crate_dir))
+        .with_config(config)
+        .generate()
+        .expect("the C API can be described in C")
+        .write_to_file(format!("{}/include/reversi.h", crate_dir));
+}
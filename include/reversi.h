@@ -0,0 +1,142 @@
+#ifndef REVERSI_H
+#define REVERSI_H
+
+/* Generated by cbindgen from src/ffi.rs when building with the `ffi` feature; do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef enum ReversiPiece {
+  REVERSI_PIECE_WHITE = 1,
+  REVERSI_PIECE_BLACK = 2,
+} ReversiPiece;
+
+typedef enum ReversiGameState {
+  REVERSI_GAME_STATE_NEW = 0,
+  REVERSI_GAME_STATE_PLAYED = 1,
+  REVERSI_GAME_STATE_PLAYED_AND_PASSED = 2,
+  REVERSI_GAME_STATE_GAME_OVER = 3,
+} ReversiGameState;
+
+/**
+ * The result of calls that can fail. The first values mirror the errors of
+ * playing a move.
+ */
+typedef enum ReversiStatus {
+  REVERSI_STATUS_OK = 0,
+  REVERSI_STATUS_INVALID_MOVE = 1,
+  REVERSI_STATUS_OUT_OF_BOUNDS = 2,
+  REVERSI_STATUS_GAME_OVER = 3,
+  REVERSI_STATUS_OUT_OF_TIME = 4,
+  REVERSI_STATUS_NULL_POINTER = 5,
+  REVERSI_STATUS_NOTHING_TO_UNDO = 6,
+} ReversiStatus;
+
+typedef enum ReversiSquare {
+  REVERSI_SQUARE_EMPTY = 0,
+  REVERSI_SQUARE_WHITE = 1,
+  REVERSI_SQUARE_BLACK = 2,
+  REVERSI_SQUARE_OUT_OF_BOUNDS = 3,
+} ReversiSquare;
+
+/**
+ * A game, owned by the caller from `reversi_game_new` or `reversi_game_clone`
+ * until it's passed to `reversi_game_free`.
+ */
+typedef struct ReversiGame ReversiGame;
+
+/**
+ * A square, counting rows and columns from 0: a1 is (0, 0) and d3 is (2, 3).
+ */
+typedef struct ReversiCoord {
+  uint8_t row;
+  uint8_t col;
+} ReversiCoord;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Starts a game from the standard position, with white to move.
+ */
+struct ReversiGame *reversi_game_new(void);
+
+/**
+ * Copies a game, or returns NULL when given NULL.
+ */
+struct ReversiGame *reversi_game_clone(const struct ReversiGame *game);
+
+/**
+ * Releases a game. NULL is ignored.
+ */
+void reversi_game_free(struct ReversiGame *game);
+
+/**
+ * The player to move, or white when given NULL.
+ */
+enum ReversiPiece reversi_game_current_player(const struct ReversiGame *game);
+
+enum ReversiGameState reversi_game_state(const struct ReversiGame *game);
+
+/**
+ * Writes up to `capacity` legal moves of the player to move to `moves`, and
+ * returns how many legal moves there are. Pass a `capacity` of 0 to only count
+ * them, in which case `moves` may be NULL.
+ *
+ * # Safety
+ *
+ * `moves` must point to at least `capacity` writable coords.
+ */
+uintptr_t reversi_game_legal_moves(const struct ReversiGame *game,
+                                   struct ReversiCoord *moves,
+                                   uintptr_t capacity);
+
+/**
+ * Plays a move for the player to move, passing for the opponent if they're
+ * left without moves.
+ */
+enum ReversiStatus reversi_game_play(struct ReversiGame *game, uint8_t row, uint8_t col);
+
+/**
+ * Takes back the last move.
+ */
+enum ReversiStatus reversi_game_undo(struct ReversiGame *game);
+
+/**
+ * The square at a row and column, or OUT_OF_BOUNDS when they're off the
+ * board or the game is NULL.
+ */
+enum ReversiSquare reversi_game_square(const struct ReversiGame *game, uint8_t row, uint8_t col);
+
+/**
+ * Writes the 64 squares to `squares`, row by row from a1 to h8.
+ *
+ * # Safety
+ *
+ * `squares` must point to 64 writable squares.
+ */
+enum ReversiStatus reversi_game_board(const struct ReversiGame *game, enum ReversiSquare *squares);
+
+/**
+ * How many discs a player, given as a `ReversiPiece`, has on the board, or 0
+ * when the piece isn't one of its values or the game is NULL.
+ */
+uint32_t reversi_game_count(const struct ReversiGame *game, uint32_t piece);
+
+/**
+ * Searches `depth` moves ahead on `threads` threads and writes the engine's
+ * choice for the player to move to `best_move`, without playing it.
+ */
+enum ReversiStatus reversi_engine_search(const struct ReversiGame *game,
+                                         uint32_t depth,
+                                         uint32_t threads,
+                                         struct ReversiCoord *best_move);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* REVERSI_H */
@@ -0,0 +1,393 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tungstenite::http::StatusCode;
+use tungstenite::{Message, WebSocket};
+
+use reversi_rust::reversi::game::{Game, GameState};
+use reversi_rust::reversi::piece::Piece;
+use reversi_rust::reversi::rules::{ParseRulesError, Rules};
+
+use crate::{game_state, parse_move};
+
+// How long a connection waits for a message before checking for updates to send
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Role {
+    Player(Piece),
+    Spectator,
+}
+
+impl Role {
+    fn name(&self) -> &'static str {
+        match self {
+            Role::Player(Piece::White) => "White",
+            Role::Player(Piece::Black) => "Black",
+            Role::Spectator => "Spectator",
+        }
+    }
+}
+
+// A game and everyone connected to it, each with a channel to send them messages
+struct Session {
+    game: Game,
+    white: Option<u64>,
+    black: Option<u64>,
+    clients: HashMap<u64, Sender<Value>>,
+}
+
+impl Session {
+    fn new(rules: Rules) -> Session {
+        Session { game: Game::with_rules(rules), white: None, black: None, clients: HashMap::new() }
+    }
+
+    fn seat(&mut self, piece: Piece) -> &mut Option<u64> {
+        match piece {
+            Piece::White => &mut self.white,
+            Piece::Black => &mut self.black,
+        }
+    }
+
+    // Clients whose connection has closed are dropped on their way out, so
+    // failed sends can be ignored
+    fn broadcast(&self, message: &Value) {
+        for client in self.clients.values() {
+            let _ = client.send(message.clone());
+        }
+    }
+
+    fn send(&self, client: u64, message: Value) {
+        if let Some(client) = self.clients.get(&client) {
+            let _ = client.send(message);
+        }
+    }
+
+    fn play(&mut self, client: u64, role: Role, text: &str) {
+        let player = match role {
+            Role::Player(player) => player,
+            Role::Spectator => return self.send(client, error("Spectators can't play")),
+        };
+        if self.game.current_turn().player != player && self.game.state() != GameState::GameOver {
+            return self.send(client, error("It's not your turn"));
+        }
+
+        let coord = match parse_move(text) {
+            Ok(coord) => coord,
+            Err(message) => return self.send(client, error(&message)),
+        };
+        if let Err(err) = self.game.try_play(coord) {
+            return self.send(client, error(&err.to_string()));
+        }
+
+        let mut state = game_state(&self.game);
+        state["type"] = json!("move");
+        state["move"] = json!(coord.to_string());
+        self.broadcast(&state);
+
+        if self.game.state() == GameState::PlayedAndPassed {
+            self.broadcast(&json!({ "type": "pass", "player": player.opponent() }));
+        }
+        if let Some(result) = self.game.result() {
+            self.broadcast(&json!({ "type": "result", "result": result }));
+        }
+    }
+}
+
+fn error(message: &str) -> Value {
+    json!({ "type": "error", "error": message })
+}
+
+// Live sessions are created by the first client to connect to them, with the
+// rules that client asked for, and forgotten once everyone has left
+#[derive(Clone, Default)]
+pub struct Lobby {
+    inner: Arc<Mutex<LobbyState>>,
+}
+
+#[derive(Default)]
+struct LobbyState {
+    sessions: HashMap<String, Session>,
+    next_client: u64,
+}
+
+impl Lobby {
+    // Clients asking for other rules than those of a running session are
+    // turned away, rather than playing a game they didn't expect
+    fn join(&self, name: &str, spectate: bool, rules: Option<Rules>, sender: Sender<Value>) -> Result<(u64, Role), String> {
+        let mut lobby = self.inner.lock().unwrap();
+        if let (Some(session), Some(rules)) = (lobby.sessions.get(name), rules) {
+            if session.game.rules() != rules {
+                return Err(format!("This game is played with {} rules", session.game.rules()));
+            }
+        }
+
+        lobby.next_client += 1;
+        let client = lobby.next_client;
+
+        let session = lobby.sessions.entry(name.to_string()).or_insert_with(|| Session::new(rules.unwrap_or_default()));
+        let role = match (spectate, session.white, session.black) {
+            (false, None, _) => Role::Player(Piece::White),
+            (false, _, None) => Role::Player(Piece::Black),
+            _ => Role::Spectator,
+        };
+        if let Role::Player(piece) = role {
+            *session.seat(piece) = Some(client);
+        }
+
+        session.broadcast(&json!({ "type": "joined", "role": role.name() }));
+
+        let mut welcome = game_state(&session.game);
+        welcome["type"] = json!("welcome");
+        welcome["role"] = json!(role.name());
+        let _ = sender.send(welcome);
+        session.clients.insert(client, sender);
+
+        Ok((client, role))
+    }
+
+    fn play(&self, name: &str, client: u64, role: Role, text: &str) {
+        if let Some(session) = self.inner.lock().unwrap().sessions.get_mut(name) {
+            session.play(client, role, text);
+        }
+    }
+
+    // A player's seat is freed when they leave, so they can reconnect to it
+    fn leave(&self, name: &str, client: u64, role: Role) {
+        let mut lobby = self.inner.lock().unwrap();
+        let session = match lobby.sessions.get_mut(name) {
+            Some(session) => session,
+            None => return,
+        };
+
+        session.clients.remove(&client);
+        if let Role::Player(piece) = role {
+            *session.seat(piece) = None;
+        }
+
+        if session.clients.is_empty() {
+            lobby.sessions.remove(name);
+        } else {
+            session.broadcast(&json!({ "type": "left", "role": role.name() }));
+        }
+    }
+}
+
+pub fn serve(listener: TcpListener, lobby: Lobby) {
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let lobby = lobby.clone();
+                thread::spawn(move || handle_connection(stream, lobby));
+            }
+            Err(err) => eprintln!("Could not accept a live connection: {}", err),
+        }
+    }
+}
+
+#[allow(clippy::result_large_err)] // the handshake callback's error type is tungstenite's
+fn handle_connection(stream: TcpStream, lobby: Lobby) {
+    let mut target = None;
+    let accepted = tungstenite::accept_hdr(stream, |request: &Request, response: Response| {
+        match parse_target(request.uri().path(), request.uri().query()) {
+            Ok(parsed) => {
+                target = Some(parsed);
+                Ok(response)
+            }
+            Err(status) => {
+                let message = match status {
+                    StatusCode::BAD_REQUEST => ParseRulesError.to_string(),
+                    _ => String::from("Live games are at /live/<name>"),
+                };
+                let mut rejected = ErrorResponse::new(Some(message));
+                *rejected.status_mut() = status;
+                Err(rejected)
+            }
+        }
+    }).ok(); // the handshake error holds on to the callback, and with it the target
+
+    let (mut socket, (name, spectate, rules)) = match (accepted, target) {
+        (Some(socket), Some(target)) => (socket, target),
+        _ => return,
+    };
+    if socket.get_ref().set_read_timeout(Some(POLL_INTERVAL)).is_err() {
+        return;
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let (client, role) = match lobby.join(&name, spectate, rules, sender) {
+        Ok(joined) => joined,
+        Err(message) => {
+            let _ = socket.send(Message::Text(error(&message).to_string()));
+            let _ = socket.close(None);
+            let _ = socket.flush();
+            return;
+        }
+    };
+
+    loop {
+        match socket.read() {
+            Ok(Message::Text(text)) => lobby.play(&name, client, role, &text),
+            Ok(Message::Close(_)) => break,
+            Ok(_) => (),
+            Err(tungstenite::Error::Io(err)) if is_timeout(&err) => (),
+            Err(_) => break,
+        }
+
+        if !send_pending(&mut socket, receiver.try_iter()) {
+            break;
+        }
+    }
+
+    lobby.leave(&name, client, role);
+}
+
+fn send_pending(socket: &mut WebSocket<TcpStream>, messages: impl Iterator<Item = Value>) -> bool {
+    for message in messages {
+        if socket.send(Message::Text(message.to_string())).is_err() {
+            return false;
+        }
+    }
+
+    true
+}
+
+fn is_timeout(err: &io::Error) -> bool {
+    matches!(err.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut)
+}
+
+// Paths look like /live/<name>, with ?spectate to join as a spectator and
+// ?rules=<rules> to choose the rules, with their words joined by commas like
+// misere,parallel. Paths that aren't live games are not found, and rules that
+// can't be parsed are a bad request.
+fn parse_target(path: &str, query: Option<&str>) -> Result<(String, bool, Option<Rules>), StatusCode> {
+    let name = path.strip_prefix("/live/").ok_or(StatusCode::NOT_FOUND)?;
+    if name.is_empty() || name.contains('/') {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let params: Vec<&str> = query.map(|query| query.split('&').collect()).unwrap_or_default();
+    let spectate = params.contains(&"spectate");
+    let rules = match params.iter().find_map(|param| param.strip_prefix("rules=")) {
+        Some(rules) => Some(rules.replace("%20", " ").replace(['+', ','], " ").parse().map_err(|_| StatusCode::BAD_REQUEST)?),
+        None => None,
+    };
+
+    Ok((name.to_string(), spectate, rules))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    use serde_json::{json, Value};
+    use tungstenite::http::StatusCode;
+    use tungstenite::{Message, WebSocket};
+
+    use reversi_rust::reversi::rules::Rules;
+
+    use super::{parse_target, serve, Lobby};
+
+    fn start() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || serve(listener, Lobby::default()));
+        format!("ws://{}", address)
+    }
+
+    fn connect(url: &str) -> WebSocket<TcpStream> {
+        let stream = TcpStream::connect(url.trim_start_matches("ws://").split('/').next().unwrap()).unwrap();
+        tungstenite::client(url, stream).unwrap().0
+    }
+
+    fn receive(socket: &mut WebSocket<TcpStream>) -> Value {
+        match socket.read().unwrap() {
+            Message::Text(text) => serde_json::from_str(&text).unwrap(),
+            other => panic!("Unexpected message: {:?}", other),
+        }
+    }
+
+    fn send_move(socket: &mut WebSocket<TcpStream>, coord: &str) {
+        socket.send(Message::Text(json!({ "move": coord }).to_string())).unwrap();
+    }
+
+    #[test]
+    fn targets_are_parsed_from_the_path() {
+        assert_eq!(parse_target("/live/club", None), Ok((String::from("club"), false, None)));
+        assert_eq!(parse_target("/live/club", Some("spectate")), Ok((String::from("club"), true, None)));
+        assert_eq!(parse_target("/live/club", Some("rules=misere,parallel")), Ok((String::from("club"), false, Some("misere parallel".parse().unwrap()))));
+        assert_eq!(parse_target("/live/club", Some("spectate&rules=misere")), Ok((String::from("club"), true, Some(Rules::misere()))));
+        assert_eq!(parse_target("/live/club", Some("rules=chess")), Err(StatusCode::BAD_REQUEST));
+        assert_eq!(parse_target("/live/", None), Err(StatusCode::NOT_FOUND));
+        assert_eq!(parse_target("/games/1", None), Err(StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn players_and_spectators_receive_moves() {
+        let url = format!("{}/live/club", start());
+
+        let mut white = connect(&url);
+        let welcome = receive(&mut white);
+        assert_eq!(welcome["type"], "welcome");
+        assert_eq!(welcome["role"], "White");
+        assert_eq!(welcome["legal_moves"], json!(["d3", "c4", "f5", "e6"]));
+
+        let mut black = connect(&url);
+        assert_eq!(receive(&mut black)["role"], "Black");
+        assert_eq!(receive(&mut white), json!({ "type": "joined", "role": "Black" }));
+
+        let mut spectator = connect(&url);
+        assert_eq!(receive(&mut spectator)["role"], "Spectator");
+        receive(&mut white);
+        receive(&mut black);
+
+        send_move(&mut black, "d3");
+        assert_eq!(receive(&mut black), json!({ "type": "error", "error": "It's not your turn" }));
+        send_move(&mut spectator, "d3");
+        assert_eq!(receive(&mut spectator)["error"], "Spectators can't play");
+
+        send_move(&mut white, "d3");
+        for socket in [&mut white, &mut black, &mut spectator] {
+            let update = receive(socket);
+            assert_eq!(update["type"], "move");
+            assert_eq!(update["move"], "d3");
+            assert_eq!(update["turn"]["player"], "Black");
+        }
+
+        send_move(&mut black, "a1");
+        assert_eq!(receive(&mut black)["error"], "Invalid move");
+    }
+
+    #[test]
+    fn sessions_are_separate() {
+        let server = start();
+
+        let mut first = connect(&format!("{}/live/first", server));
+        let mut second = connect(&format!("{}/live/second", server));
+
+        assert_eq!(receive(&mut first)["role"], "White");
+        assert_eq!(receive(&mut second)["role"], "White");
+    }
+
+    #[test]
+    fn sessions_keep_the_rules_they_were_created_with() {
+        let url = format!("{}/live/club", start());
+
+        let mut white = connect(&format!("{}?rules=misere", url));
+        assert_eq!(receive(&mut white)["rules"], "misere");
+
+        let mut black = connect(&url);
+        assert_eq!(receive(&mut black)["rules"], "misere");
+
+        let mut other = connect(&format!("{}?rules=standard", url));
+        assert_eq!(receive(&mut other), json!({ "type": "error", "error": "This game is played with misere rules" }));
+    }
+}
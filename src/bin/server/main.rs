@@ -0,0 +1,353 @@
+mod live;
+
+use std::collections::HashMap;
+use std::env;
+use std::net::TcpListener;
+use std::process;
+use std::thread;
+
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Response, Server};
+
+use reversi_rust::reversi::coord::Coord;
+use reversi_rust::reversi::engine::Engine;
+use reversi_rust::reversi::game::{Game, PlayError};
+use reversi_rust::reversi::piece::Piece;
+use reversi_rust::reversi::rules::{ParseRulesError, Rules};
+
+const USAGE: &str = "\
+Usage: server [options]
+  --address <host:port>       address to listen on (defaults to 127.0.0.1:8080)
+  --live-address <host:port>  address to listen on for live games (defaults to 127.0.0.1:8081)
+
+Endpoints:
+  POST   /games                   start a game
+  GET    /games/<id>              get the state of a game
+  DELETE /games/<id>              forget a game
+  POST   /games/<id>/moves        play a move, with a body like {\"move\": \"d3\"}
+  POST   /games/<id>/undo         take back the last move
+  POST   /games/<id>/engine-move  let the engine play, with an optional body like {\"depth\": 4}
+
+Live games are played over WebSockets at ws://<live address>/live/<name>. The first
+two clients to connect play white and black, and everyone else watches; add
+?spectate to the address to watch a game with a free seat. The first client can
+choose the rules with ?rules=<rules>, like ?rules=misere,parallel.";
+
+// Deeper searches would keep the single server thread busy for too long
+const MAX_ENGINE_DEPTH: u32 = 8;
+
+fn main() {
+    let (address, live_address) = match parse_args(env::args().skip(1)) {
+        Ok(addresses) => addresses,
+        Err(message) => {
+            eprintln!("{}", message);
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    let server = match Server::http(&address) {
+        Ok(server) => server,
+        Err(err) => {
+            eprintln!("Could not listen on {}: {}", address, err);
+            process::exit(1);
+        }
+    };
+    println!("Listening on http://{}", address);
+
+    let live_listener = match TcpListener::bind(&live_address) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("Could not listen on {}: {}", live_address, err);
+            process::exit(1);
+        }
+    };
+    println!("Listening for live games on ws://{}", live_address);
+    thread::spawn(move || live::serve(live_listener, live::Lobby::default()));
+
+    let mut api = Api::default();
+    let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let (status, value) = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => api.handle(request.method(), request.url(), &body),
+            Err(_) => error(400, "The request body is not valid UTF-8"),
+        };
+
+        let response = Response::from_string(value.to_string()).with_status_code(status).with_header(content_type.clone());
+        if let Err(err) = request.respond(response) {
+            eprintln!("Could not respond: {}", err);
+        }
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<(String, String), String> {
+    let mut address = String::from("127.0.0.1:8080");
+    let mut live_address = String::from("127.0.0.1:8081");
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--address" => address = args.next().ok_or("Missing value for --address")?,
+            "--live-address" => live_address = args.next().ok_or("Missing value for --live-address")?,
+            _ => return Err(format!("Unknown option: {}", arg)),
+        }
+    }
+
+    Ok((address, live_address))
+}
+
+// Games are kept in memory by ID until they're deleted or the server stops
+#[derive(Default)]
+struct Api {
+    games: HashMap<u64, Game>,
+    next_id: u64,
+}
+
+impl Api {
+    fn handle(&mut self, method: &Method, url: &str, body: &str) -> (u16, Value) {
+        let path = url.split('?').next().unwrap_or_default();
+        let segments: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+
+        match (method, segments.as_slice()) {
+            (Method::Post, ["games"]) => self.create(body),
+            (_, ["games", id, rest @ ..]) => {
+                let id = match id.parse::<u64>() {
+                    Ok(id) if self.games.contains_key(&id) => id,
+                    _ => return error(404, "No such game"),
+                };
+
+                match (method, rest) {
+                    (Method::Get, []) => (200, self.state(id)),
+                    (Method::Delete, []) => {
+                        self.games.remove(&id);
+                        (200, json!({ "id": id }))
+                    }
+                    (Method::Post, ["moves"]) => self.play(id, body),
+                    (Method::Post, ["undo"]) => self.undo(id),
+                    (Method::Post, ["engine-move"]) => self.play_engine_move(id, body),
+                    _ => error(404, "No such endpoint"),
+                }
+            }
+            _ => error(404, "No such endpoint"),
+        }
+    }
+
+    // An empty body starts a game with the standard rules
+    fn create(&mut self, body: &str) -> (u16, Value) {
+        let rules = match body.trim() {
+            "" => Rules::default(),
+            body => match serde_json::from_str::<Value>(body) {
+                Ok(value) => match value.get("rules") {
+                    None => Rules::default(),
+                    Some(rules) => match rules.as_str().map(str::parse::<Rules>) {
+                        Some(Ok(rules)) => rules,
+                        _ => return error(400, &ParseRulesError.to_string()),
+                    },
+                },
+                Err(err) => return error(400, &err.to_string()),
+            },
+        };
+
+        self.next_id += 1;
+        self.games.insert(self.next_id, Game::with_rules(rules));
+        (201, self.state(self.next_id))
+    }
+
+    fn play(&mut self, id: u64, body: &str) -> (u16, Value) {
+        let coord = match parse_move(body) {
+            Ok(coord) => coord,
+            Err(message) => return error(400, &message),
+        };
+
+        match self.games.get_mut(&id).unwrap().try_play(coord) {
+            Ok(()) => (200, self.state(id)),
+            Err(err) => play_error(err),
+        }
+    }
+
+    fn undo(&mut self, id: u64) -> (u16, Value) {
+        match self.games.get_mut(&id).unwrap().undo() {
+            Some(_) => (200, self.state(id)),
+            None => error(409, "There are no moves to undo"),
+        }
+    }
+
+    // An empty body searches at the engine's default depth
+    fn play_engine_move(&mut self, id: u64, body: &str) -> (u16, Value) {
+        let depth = match body.trim() {
+            "" => Engine::DEFAULT_DEPTH,
+            body => match serde_json::from_str::<Value>(body) {
+                Ok(value) => match value.get("depth") {
+                    None => Engine::DEFAULT_DEPTH,
+                    Some(depth) => match depth.as_u64() {
+                        Some(depth) if (1..=MAX_ENGINE_DEPTH as u64).contains(&depth) => depth as u32,
+                        _ => return error(400, &format!("The depth must be between 1 and {}", MAX_ENGINE_DEPTH)),
+                    },
+                },
+                Err(err) => return error(400, &err.to_string()),
+            },
+        };
+
+        let game = self.games.get_mut(&id).unwrap();
+        let coord = match Engine::new(depth).choose_move(game, None) {
+            Some(coord) => coord,
+            None => return play_error(PlayError::GameOver), // the engine always finds a move while the game is on
+        };
+
+        match game.try_play(coord) {
+            Ok(()) => {
+                let mut state = self.state(id);
+                state["move"] = json!(coord.to_string());
+                (200, state)
+            }
+            Err(err) => play_error(err),
+        }
+    }
+
+    fn state(&self, id: u64) -> Value {
+        let mut state = game_state(&self.games[&id]);
+        state["id"] = json!(id);
+        state
+    }
+}
+
+// Everything a client needs to draw the game and tell whose turn it is
+fn game_state(game: &Game) -> Value {
+    let notation = |coords: &mut dyn Iterator<Item = &Coord>| coords.map(Coord::to_string).collect::<Vec<String>>();
+    let legal_moves = match game.result() {
+        Some(_) => Vec::new(),
+        None => notation(&mut game.current_turn().valid_moves.iter().map(|mv| mv.coord())),
+    };
+
+    json!({
+        "board": game.board(),
+        "turn": game.current_turn(),
+        "legal_moves": legal_moves,
+        "state": game.state(),
+        "result": game.result(),
+        "history": notation(&mut game.history().iter()),
+        "rules": game.rules().to_string(),
+        "discs": {
+            "white": game.board().count_pieces(Piece::White),
+            "black": game.board().count_pieces(Piece::Black),
+        },
+        "stable_discs": {
+            "white": game.board().stable_discs(Piece::White).len(),
+            "black": game.board().stable_discs(Piece::Black).len(),
+        },
+    })
+}
+
+fn parse_move(body: &str) -> Result<Coord, String> {
+    let value = serde_json::from_str::<Value>(body).map_err(|err| err.to_string())?;
+    match value["move"].as_str().map(str::parse::<Coord>) {
+        Some(Ok(coord)) => Ok(coord),
+        Some(Err(err)) => Err(err.to_string()),
+        None => Err(String::from("Expected a message like {\"move\": \"d3\"}")),
+    }
+}
+
+fn error(status: u16, message: &str) -> (u16, Value) {
+    (status, json!({ "error": message }))
+}
+
+// Moves that can't be played are the client's mistake, while moves in a game
+// that has ended conflict with the game's state
+fn play_error(err: PlayError) -> (u16, Value) {
+    let status = match err {
+        PlayError::InvalidMove | PlayError::OutOfBounds => 422,
+        PlayError::GameOver | PlayError::OutOfTime => 409,
+    };
+
+    error(status, &err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tiny_http::Method;
+
+    use super::Api;
+
+    #[test]
+    fn games_are_created_and_fetched_by_id() {
+        let mut api = Api::default();
+
+        let (status, created) = api.handle(&Method::Post, "/games", "");
+        assert_eq!(status, 201);
+        assert_eq!(created["id"], 1);
+        assert_eq!(created["state"], "New");
+        assert_eq!(created["turn"]["player"], "White");
+        assert_eq!(created["legal_moves"], json!(["d3", "c4", "f5", "e6"]));
+        assert_eq!(created["discs"], json!({ "white": 2, "black": 2 }));
+
+        let (status, fetched) = api.handle(&Method::Get, "/games/1", "");
+        assert_eq!(status, 200);
+        assert_eq!(fetched, created);
+
+        assert_eq!(api.handle(&Method::Get, "/games/2", "").0, 404);
+        assert_eq!(api.handle(&Method::Get, "/players", "").0, 404);
+    }
+
+    #[test]
+    fn games_can_be_created_with_other_rules() {
+        let mut api = Api::default();
+
+        assert_eq!(api.handle(&Method::Post, "/games", "").1["rules"], "standard");
+        assert_eq!(api.handle(&Method::Post, "/games", r#"{"rules": "misere"}"#).1["rules"], "misere");
+        assert_eq!(api.handle(&Method::Post, "/games", r#"{"rules": "chess"}"#).0, 400);
+    }
+
+    #[test]
+    fn moves_are_played_and_undone() {
+        let mut api = Api::default();
+        api.handle(&Method::Post, "/games", "");
+
+        let (status, played) = api.handle(&Method::Post, "/games/1/moves", r#"{"move": "d3"}"#);
+        assert_eq!(status, 200);
+        assert_eq!(played["history"], json!(["d3"]));
+        assert_eq!(played["turn"]["player"], "Black");
+
+        let (status, undone) = api.handle(&Method::Post, "/games/1/undo", "");
+        assert_eq!(status, 200);
+        assert_eq!(undone["history"], json!([]));
+        assert_eq!(api.handle(&Method::Post, "/games/1/undo", "").0, 409);
+    }
+
+    #[test]
+    fn bad_moves_are_reported_as_errors() {
+        let mut api = Api::default();
+        api.handle(&Method::Post, "/games", "");
+
+        let (status, body) = api.handle(&Method::Post, "/games/1/moves", r#"{"move": "a1"}"#);
+        assert_eq!(status, 422);
+        assert_eq!(body["error"], "Invalid move");
+
+        assert_eq!(api.handle(&Method::Post, "/games/1/moves", r#"{"move": "z9"}"#).0, 422);
+        assert_eq!(api.handle(&Method::Post, "/games/1/moves", r#"{"move": "d"}"#).0, 400);
+        assert_eq!(api.handle(&Method::Post, "/games/1/moves", "d3").0, 400);
+    }
+
+    #[test]
+    fn engine_plays_a_legal_move() {
+        let mut api = Api::default();
+        api.handle(&Method::Post, "/games", "");
+
+        let (status, body) = api.handle(&Method::Post, "/games/1/engine-move", r#"{"depth": 2}"#);
+        assert_eq!(status, 200);
+        assert!(["d3", "c4", "f5", "e6"].contains(&body["move"].as_str().unwrap()));
+        assert_eq!(body["history"], json!([body["move"]]));
+
+        assert_eq!(api.handle(&Method::Post, "/games/1/engine-move", r#"{"depth": 99}"#).0, 400);
+    }
+
+    #[test]
+    fn deleted_games_are_forgotten() {
+        let mut api = Api::default();
+        api.handle(&Method::Post, "/games", "");
+
+        assert_eq!(api.handle(&Method::Delete, "/games/1", "").0, 200);
+        assert_eq!(api.handle(&Method::Get, "/games/1", "").0, 404);
+    }
+}
@@ -0,0 +1,299 @@
+// The C API. Doc comments here are copied into include/reversi.h, which is
+// generated by the build script when the `ffi` feature is enabled.
+
+use crate::reversi::board::Board;
+use crate::reversi::coord::Coord;
+use crate::reversi::engine::Engine;
+use crate::reversi::game::{Game, GameState, PlayError};
+use crate::reversi::piece::{BoardSquare, Piece};
+
+/// A game, owned by the caller from `reversi_game_new` or `reversi_game_clone`
+/// until it's passed to `reversi_game_free`.
+pub struct ReversiGame {
+    game: Game,
+}
+
+/// The result of calls that can fail. The first values mirror the errors of
+/// playing a move.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReversiStatus {
+    Ok = 0,
+    InvalidMove = 1,
+    OutOfBounds = 2,
+    GameOver = 3,
+    OutOfTime = 4,
+    NullPointer = 5,
+    NothingToUndo = 6,
+}
+
+impl From<PlayError> for ReversiStatus {
+    fn from(err: PlayError) -> Self {
+        match err {
+            PlayError::InvalidMove => ReversiStatus::InvalidMove,
+            PlayError::OutOfBounds => ReversiStatus::OutOfBounds,
+            PlayError::GameOver => ReversiStatus::GameOver,
+            PlayError::OutOfTime => ReversiStatus::OutOfTime,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReversiPiece {
+    White = 1,
+    Black = 2,
+}
+
+impl From<Piece> for ReversiPiece {
+    fn from(piece: Piece) -> Self {
+        match piece {
+            Piece::White => ReversiPiece::White,
+            Piece::Black => ReversiPiece::Black,
+        }
+    }
+}
+
+impl ReversiPiece {
+    // Pieces are taken from C as plain integers, since any other value in a
+    // Rust enum would be undefined behavior
+    fn from_c(value: u32) -> Option<Piece> {
+        match value {
+            value if value == ReversiPiece::White as u32 => Some(Piece::White),
+            value if value == ReversiPiece::Black as u32 => Some(Piece::Black),
+            _ => None,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReversiSquare {
+    Empty = 0,
+    White = 1,
+    Black = 2,
+    OutOfBounds = 3,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReversiGameState {
+    New = 0,
+    Played = 1,
+    PlayedAndPassed = 2,
+    GameOver = 3,
+}
+
+/// A square, counting rows and columns from 0: a1 is (0, 0) and d3 is (2, 3).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReversiCoord {
+    pub row: u8,
+    pub col: u8,
+}
+
+impl From<Coord> for ReversiCoord {
+    fn from(coord: Coord) -> Self {
+        ReversiCoord { row: coord.row as u8, col: coord.col as u8 }
+    }
+}
+
+/// Starts a game from the standard position, with white to move.
+#[no_mangle]
+pub extern "C" fn reversi_game_new() -> Box<ReversiGame> {
+    Box::new(ReversiGame { game: Game::new() })
+}
+
+/// Copies a game, or returns NULL when given NULL.
+#[no_mangle]
+pub extern "C" fn reversi_game_clone(game: Option<&ReversiGame>) -> Option<Box<ReversiGame>> {
+    game.map(|game| Box::new(ReversiGame { game: game.game.clone() }))
+}
+
+/// Releases a game. NULL is ignored.
+#[no_mangle]
+pub extern "C" fn reversi_game_free(game: Option<Box<ReversiGame>>) {
+    drop(game);
+}
+
+/// The player to move, or white when given NULL.
+#[no_mangle]
+pub extern "C" fn reversi_game_current_player(game: Option<&ReversiGame>) -> ReversiPiece {
+    game.map_or(ReversiPiece::White, |game| game.game.current_turn().player.into())
+}
+
+#[no_mangle]
+pub extern "C" fn reversi_game_state(game: Option<&ReversiGame>) -> ReversiGameState {
+    match game.map(|game| game.game.state()) {
+        Some(GameState::New) | None => ReversiGameState::New,
+        Some(GameState::Played) => ReversiGameState::Played,
+        Some(GameState::PlayedAndPassed) => ReversiGameState::PlayedAndPassed,
+        Some(GameState::GameOver) => ReversiGameState::GameOver,
+    }
+}
+
+/// Writes up to `capacity` legal moves of the player to move to `moves`, and
+/// returns how many legal moves there are. Pass a `capacity` of 0 to only count
+/// them, in which case `moves` may be NULL.
+///
+/// # Safety
+///
+/// `moves` must point to at least `capacity` writable coords.
+#[no_mangle]
+pub unsafe extern "C" fn reversi_game_legal_moves(game: Option<&ReversiGame>, moves: *mut ReversiCoord, capacity: usize) -> usize {
+    let game = match game {
+        Some(game) if game.game.state() != GameState::GameOver => &game.game,
+        _ => return 0,
+    };
+    let valid_moves = &game.current_turn().valid_moves;
+
+    if !moves.is_null() {
+        for (index, mv) in valid_moves.iter().take(capacity).enumerate() {
+            moves.add(index).write((*mv.coord()).into());
+        }
+    }
+
+    valid_moves.len()
+}
+
+/// Plays a move for the player to move, passing for the opponent if they're
+/// left without moves.
+#[no_mangle]
+pub extern "C" fn reversi_game_play(game: Option<&mut ReversiGame>, row: u8, col: u8) -> ReversiStatus {
+    match game {
+        Some(game) => match game.game.try_play((row as usize, col as usize).into()) {
+            Ok(()) => ReversiStatus::Ok,
+            Err(err) => err.into(),
+        },
+        None => ReversiStatus::NullPointer,
+    }
+}
+
+/// Takes back the last move.
+#[no_mangle]
+pub extern "C" fn reversi_game_undo(game: Option<&mut ReversiGame>) -> ReversiStatus {
+    match game.map(|game| game.game.undo()) {
+        Some(Some(_)) => ReversiStatus::Ok,
+        Some(None) => ReversiStatus::NothingToUndo,
+        None => ReversiStatus::NullPointer,
+    }
+}
+
+/// The square at a row and column, or OUT_OF_BOUNDS when they're off the
+/// board or the game is NULL.
+#[no_mangle]
+pub extern "C" fn reversi_game_square(game: Option<&ReversiGame>, row: u8, col: u8) -> ReversiSquare {
+    let game = match game {
+        Some(game) => &game.game,
+        None => return ReversiSquare::OutOfBounds,
+    };
+
+    match game.board().get_coord_square_at((row as usize, col as usize).into()) {
+        (_, BoardSquare::Unplayed) => ReversiSquare::Empty,
+        (_, BoardSquare::Played(Piece::White)) => ReversiSquare::White,
+        (_, BoardSquare::Played(Piece::Black)) => ReversiSquare::Black,
+        (_, BoardSquare::OutOfBounds) => ReversiSquare::OutOfBounds,
+    }
+}
+
+/// Writes the 64 squares to `squares`, row by row from a1 to h8.
+///
+/// # Safety
+///
+/// `squares` must point to 64 writable squares.
+#[no_mangle]
+pub unsafe extern "C" fn reversi_game_board(game: Option<&ReversiGame>, squares: *mut ReversiSquare) -> ReversiStatus {
+    if game.is_none() || squares.is_null() {
+        return ReversiStatus::NullPointer;
+    }
+
+    // The buffer may hold anything, so it's written to without being read
+    for (index, coord) in Board::coords().enumerate() {
+        squares.add(index).write(reversi_game_square(game, coord.row as u8, coord.col as u8));
+    }
+
+    ReversiStatus::Ok
+}
+
+/// How many discs a player, given as a `ReversiPiece`, has on the board, or 0
+/// when the piece isn't one of its values or the game is NULL.
+#[no_mangle]
+pub extern "C" fn reversi_game_count(game: Option<&ReversiGame>, piece: u32) -> u32 {
+    match (game, ReversiPiece::from_c(piece)) {
+        (Some(game), Some(piece)) => game.game.board().count_pieces(piece) as u32,
+        _ => 0,
+    }
+}
+
+/// Searches `depth` moves ahead on `threads` threads and writes the engine's
+/// choice for the player to move to `best_move`, without playing it.
+#[no_mangle]
+pub extern "C" fn reversi_engine_search(game: Option<&ReversiGame>, depth: u32, threads: u32, best_move: Option<&mut ReversiCoord>) -> ReversiStatus {
+    let (game, best_move) = match (game, best_move) {
+        (Some(game), Some(best_move)) => (&game.game, best_move),
+        _ => return ReversiStatus::NullPointer,
+    };
+
+    let engine = Engine::new(depth.max(1)).with_threads(threads.max(1) as usize);
+    match engine.choose_move(game, None) {
+        Some(coord) => {
+            *best_move = coord.into();
+            ReversiStatus::Ok
+        }
+        None => ReversiStatus::GameOver,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use super::*;
+
+    #[test]
+    fn games_are_played_through_handles() {
+        let mut game = reversi_game_new();
+
+        assert_eq!(unsafe { reversi_game_legal_moves(Some(&game), ptr::null_mut(), 0) }, 4);
+        assert_eq!(reversi_game_play(Some(&mut game), 2, 3), ReversiStatus::Ok);
+        assert_eq!(reversi_game_play(Some(&mut game), 0, 0), ReversiStatus::InvalidMove);
+        assert_eq!(reversi_game_play(Some(&mut game), 9, 9), ReversiStatus::OutOfBounds);
+        assert_eq!(reversi_game_square(Some(&game), 2, 3), ReversiSquare::White);
+        assert_eq!(reversi_game_count(Some(&game), ReversiPiece::White as u32), 4);
+        assert_eq!(reversi_game_count(Some(&game), 7), 0);
+        assert_eq!(reversi_game_current_player(Some(&game)), ReversiPiece::Black);
+
+        let copy = reversi_game_clone(Some(&game)).unwrap();
+        assert_eq!(reversi_game_undo(Some(&mut game)), ReversiStatus::Ok);
+        assert_eq!(reversi_game_undo(Some(&mut game)), ReversiStatus::NothingToUndo);
+        assert_eq!(reversi_game_square(Some(&copy), 2, 3), ReversiSquare::White);
+
+        reversi_game_free(Some(game));
+        reversi_game_free(Some(copy));
+        reversi_game_free(None);
+    }
+
+    #[test]
+    fn legal_moves_and_board_are_written_to_buffers() {
+        let game = reversi_game_new();
+        let mut moves = [ReversiCoord { row: 0, col: 0 }; 2];
+        let mut squares = [ReversiSquare::OutOfBounds; 64];
+
+        assert_eq!(unsafe { reversi_game_legal_moves(Some(&game), moves.as_mut_ptr(), moves.len()) }, 4);
+        assert_eq!(moves, [ReversiCoord { row: 2, col: 3 }, ReversiCoord { row: 3, col: 2 }]);
+
+        assert_eq!(unsafe { reversi_game_board(Some(&game), squares.as_mut_ptr()) }, ReversiStatus::Ok);
+        assert_eq!(squares.iter().filter(|square| **square == ReversiSquare::Empty).count(), 60);
+        assert_eq!(squares[3 * 8 + 3], ReversiSquare::Black); // d4
+    }
+
+    #[test]
+    fn null_handles_are_rejected() {
+        let mut best_move = ReversiCoord { row: 0, col: 0 };
+
+        assert_eq!(reversi_game_play(None, 2, 3), ReversiStatus::NullPointer);
+        assert_eq!(reversi_engine_search(None, 2, 1, Some(&mut best_move)), ReversiStatus::NullPointer);
+        assert_eq!(unsafe { reversi_game_board(None, ptr::null_mut()) }, ReversiStatus::NullPointer);
+        assert!(reversi_game_clone(None).is_none());
+    }
+}
@@ -1,17 +1,620 @@
-pub mod reversi;
+use reversi_rust::reversi;
+#[cfg(feature = "tui")]
+mod tui;
 
-use reversi::game::Game;
+use std::env;
+use std::fs;
+use std::net::TcpListener;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::process;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reversi::analysis::{analyze, AnalysisOptions, Score};
+use reversi::clock::{Clocks, TimeControl};
+use reversi::coord::Coord;
+use reversi::driver::MatchDriver;
+use reversi::engine::{Engine, SearchLimits};
+use reversi::features::Features;
+use reversi::game::{Game, GameResult, GameState};
+use reversi::network::{play_remote_move, Connection, NetMessage, NetworkError};
+use reversi::piece::Piece;
+use reversi::puzzle::{self, PuzzleOptions};
+use reversi::random::Rng;
+#[cfg(feature = "raster")]
+use reversi::raster::{self, RasterOptions};
+use reversi::render::{BoardRenderer, RenderOptions};
+use reversi::rules::{Rules, Start, WinCondition};
+use reversi::save::{Players, SavedGame};
+use reversi::svg::{self, SvgOptions};
+use reversi::time_manager::TimeManager;
+
+const USAGE: &str = "\
+Usage: reversi-rust [options]
+  --load <file>            resume a saved game; names and engines can be changed, but not clocks or rules
+  --white <name>           name of the white player
+  --black <name>           name of the black player
+  --white-engine <depth>   let the engine play white, searching <depth> moves ahead
+  --black-engine <depth>   let the engine play black, searching <depth> moves ahead
+  --threads <count>        number of threads used by the engines, hints and analysis (defaults to 1)
+  --tui                    play in a full-screen terminal interface
+  --host <port>            host a game over the network, playing white
+  --join <host:port>       join a game hosted over the network, playing black
+  --analyze <depth>        analyze the game, searching <depth> moves ahead, and exit (use with --load)
+  --make-puzzles <file>    find only-winning-move puzzles in the loaded game (with --load) or in self-played games, adding them to <file>
+  --puzzles <file>         solve the puzzles in <file>
+  --rules <rules>          standard, or misere where the player with fewer discs wins
+  --start <start>          crossed (the usual start), parallel, original (the first four moves fill the center), xot (a random balanced opening) or xot-<n>
+  --clock <control>        play with clocks, like \"sudden-death 300\", \"fischer 300+5\" or \"byo-yomi 600 30x5\"";
+
+// Enough to reach the end of any game, so timed hints are only limited by time
+const MAX_HINT_DEPTH: u32 = 60;
+
+// Enough self-played games to find a good number of puzzles in a few seconds
+const SELF_PLAY_PUZZLE_GAMES: usize = 20;
+
+const HELP: &str = "\
+Commands:
+  <move>           play a move, like d3
+  save <file>      save the game to a file
+  load <file>      load a game from a file
+  svg <file>       draw the board to an SVG file, numbering discs by move
+  png <file>       draw the board to a PNG image
+  gif <file>       animate the whole game to a GIF image
+  hint [depth]     rank the valid moves by the engine's evaluation, searching [depth] moves ahead
+  hint <seconds>s  rank the valid moves, searching for about that long, like hint 5s
+  analyze [depth]  compare the moves played with the engine's, flagging mistakes
+  features         show features of the position, like mobility, frontier discs and edges
+  stable           mark the discs that can't be flipped anymore, like {B}, or stop marking them
+  help             show this message
+  quit             leave the game";
+
+enum Mode {
+    Local,
+    Tui,
+    Host(u16),
+    Join(String),
+    Analyze(Engine),
+    MakePuzzles { path: String, from_game: bool },
+    Puzzles(String),
+}
 
 fn main() {
-    let mut game = Game::new();
+    let (session, mode, threads) = match parse_args(env::args().skip(1)) {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            eprintln!("{}", message);
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    match mode {
+        Mode::Local => (),
+        Mode::Tui => return run_tui(MatchDriver::new(session)),
+        Mode::Host(_) | Mode::Join(_) => return run_network(session, mode),
+        Mode::Analyze(engine) => {
+            print!("{}", analyze(&session.game, &AnalysisOptions { engine, ..AnalysisOptions::default() }));
+            return;
+        }
+        Mode::MakePuzzles { path, from_game } => return make_puzzles(&session.game, &path, from_game),
+        Mode::Puzzles(path) => return run_puzzles(&path),
+    }
+
+    let mut driver = MatchDriver::new(session);
+    println!("{}", HELP);
+
+    let mut render_options = render_options();
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    loop {
+        println!();
+        println!("{}", BoardRenderer::new(driver.game(), render_options));
+        print_status(driver.session());
+
+        let mover = driver.current_player().name.clone();
+        if let Some(result) = driver.play_engine_move() {
+            match result {
+                Ok(coord) => println!("{} plays {}", mover, coord),
+                Err(err) => println!("{}", err),
+            }
+            continue;
+        }
+
+        print!("> ");
+        let _ = io::stdout().flush();
+
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ => break, // end of input
+        };
+
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (None, _) => (),
+            (Some("quit"), _) | (Some("exit"), _) => break,
+            (Some("help"), _) => println!("{}", HELP),
+            (Some("hint"), limit) => match hint_limits(limit) {
+                Some(limits) => print_hints(driver.game(), &Engine::new(limits.max_depth).with_threads(threads), &limits),
+                None => println!("Invalid depth or time"),
+            },
+            (Some("analyze"), depth) => match depth.map_or(Ok(Engine::DEFAULT_DEPTH), str::parse) {
+                Ok(depth) if depth > 0 => print!("{}", analyze(driver.game(), &AnalysisOptions { engine: Engine::new(depth).with_threads(threads), ..AnalysisOptions::default() })),
+                _ => println!("Invalid depth"),
+            },
+            (Some("features"), _) => println!("{}", Features::of(driver.game().board())),
+            (Some("stable"), _) => render_options.stable = !render_options.stable,
+            (Some("save"), Some(path)) => match driver.session().save(path) {
+                Ok(()) => println!("Game saved to {}", path),
+                Err(err) => println!("{}", err),
+            },
+            (Some("load"), Some(path)) => match SavedGame::load(path) {
+                Ok(loaded) => {
+                    driver = MatchDriver::new(loaded);
+                    println!("Game loaded from {}", path);
+                }
+                Err(err) => println!("{}", err),
+            },
+            (Some("svg"), Some(path)) => match export_svg(driver.game(), path) {
+                Ok(()) => println!("Board drawn to {}", path),
+                Err(err) => println!("{}", err),
+            },
+            (Some(format @ "png"), Some(path)) | (Some(format @ "gif"), Some(path)) => match export_raster(driver.game(), format, path) {
+                Ok(()) => println!("Board drawn to {}", path),
+                Err(message) => println!("{}", message),
+            },
+            (Some("save" | "load" | "svg" | "png" | "gif"), None) => println!("Please provide a file name"),
+            (Some(text), _) => match text.parse::<Coord>() {
+                Ok(coord) => {
+                    if let Err(err) = driver.play(coord) {
+                        println!("{}", err);
+                    }
+                }
+                Err(err) => {
+                    driver.check_time();
+                    println!("{}", err);
+                }
+            },
+        }
+    }
+}
+
+// A depth, or a time in seconds like "5s", in which case the search goes as
+// deep as it can
+fn hint_limits(limit: Option<&str>) -> Option<SearchLimits> {
+    match limit {
+        None => Some(SearchLimits { max_depth: Engine::DEFAULT_DEPTH, ..SearchLimits::default() }),
+        Some(text) if text.ends_with('s') => {
+            let seconds = text.trim_end_matches('s').parse::<f64>().ok().filter(|seconds| seconds.is_finite() && *seconds > 0.0)?;
+            let time = Duration::from_secs_f64(seconds);
+            Some(SearchLimits { max_depth: MAX_HINT_DEPTH, time: Some(TimeManager::new(time, time)), stop: None })
+        }
+        Some(text) => text.parse().ok().filter(|depth| *depth > 0).map(|depth| SearchLimits { max_depth: depth, ..SearchLimits::default() }),
+    }
+}
+
+fn print_hints(game: &Game, engine: &Engine, limits: &SearchLimits) {
+    let ranked = engine.rank_moves(game, limits);
+    match ranked.is_empty() {
+        true => println!("There are no moves to rank"),
+        false => println!("Moves for {}, best first:", game.current_turn().player),
+    }
+
+    for (index, ranked_move) in ranked.iter().enumerate() {
+        let pv: Vec<String> = ranked_move.pv.iter().map(Coord::to_string).collect();
+        println!("{:>3}. {}  {:<10} depth {:<3} {}", index + 1, ranked_move.coord, Score::from_search(ranked_move.score).to_string(), ranked_move.depth, pv.join(" "));
+    }
+}
+
+// The last move is highlighted, like in the terminal board
+fn export_svg(game: &Game, path: &str) -> io::Result<()> {
+    let options = SvgOptions {
+        move_numbers: true,
+        highlights: game.last_outcome().map(|outcome| *outcome.coord()).into_iter().collect(),
+        ..SvgOptions::default()
+    };
+
+    fs::write(path, svg::game_to_svg(game, &options))
+}
+
+#[cfg(feature = "raster")]
+fn export_raster(game: &Game, format: &str, path: &str) -> Result<(), String> {
+    let file = io::BufWriter::new(fs::File::create(path).map_err(|err| err.to_string())?);
+    let options = RasterOptions::default();
+
+    let result = match format {
+        "png" => raster::board_to_png(game.board(), &options, file),
+        _ => raster::game_to_gif(game, &options, file),
+    };
+    result.map_err(|err| err.to_string())
+}
+
+#[cfg(not(feature = "raster"))]
+fn export_raster(_game: &Game, _format: &str, _path: &str) -> Result<(), String> {
+    Err(String::from("This build doesn't include image export, enable the `raster` feature to use it"))
+}
+
+// Colors are only used when writing to a terminal that doesn't opt out of them
+fn render_options() -> RenderOptions {
+    RenderOptions {
+        legal_moves: true,
+        last_move: true,
+        flipped: true,
+        color: io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none(),
+        ..RenderOptions::default()
+    }
+}
+
+// The host plays white and the joining player plays black; every move is sent
+// along with the position it led to, so both sides can tell if their games
+// ever differ
+fn run_network(mut session: SavedGame, mode: Mode) {
+    let (local, connection) = match mode {
+        Mode::Host(port) => {
+            println!("Waiting for the other player to join on port {}...", port);
+            let connection = TcpListener::bind(("0.0.0.0", port)).map_err(NetworkError::from).and_then(|listener| Connection::accept(&listener));
+            (Piece::White, connection)
+        }
+        Mode::Join(address) => (Piece::Black, Connection::connect(address.as_str())),
+        Mode::Local | Mode::Tui | Mode::Analyze(_) | Mode::MakePuzzles { .. } | Mode::Puzzles(_) => unreachable!("only network modes play over the network"),
+    };
+
+    let mut connection = match connection {
+        Ok(connection) => connection,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    };
+    match connection.handshake(&session.players.get(local).name, session.game.position_hash(), session.game.rules()) {
+        Ok(name) => session.players.get_mut(local.opponent()).name = name,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
+
+    let mut driver = MatchDriver::new(session);
+    println!("Playing {} against {}", local, driver.session().players.get(local.opponent()).name);
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    while driver.game().state() != GameState::GameOver {
+        println!();
+        println!("{}", BoardRenderer::new(driver.game(), render_options()));
+        print_status(driver.session());
+
+        if driver.game().current_turn().player != local {
+            let received = match connection.receive() {
+                Ok(NetMessage::Move { coord, hash }) => play_remote_move(&mut driver, coord, hash).map(|_| coord),
+                Ok(NetMessage::Quit) => {
+                    println!("{} left the game", driver.current_player().name);
+                    return;
+                }
+                Ok(message) => Err(NetworkError::Protocol(format!("Unexpected message: {}", message))),
+                Err(err) => Err(err),
+            };
+
+            match received {
+                Ok(coord) => println!("{} plays {}", driver.session().players.get(local.opponent()).name, coord),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    process::exit(1);
+                }
+            }
+            continue;
+        }
+
+        let coord = match driver.play_engine_move() {
+            Some(Ok(coord)) => coord,
+            Some(Err(err)) => {
+                println!("{}", err);
+                continue;
+            }
+            None => {
+                print!("> ");
+                let _ = io::stdout().flush();
+
+                let line = match lines.next() {
+                    Some(Ok(line)) => line,
+                    _ => String::from("quit"), // end of input
+                };
+                match line.trim() {
+                    "" => continue,
+                    "quit" | "exit" => {
+                        let _ = connection.send(&NetMessage::Quit);
+                        return;
+                    }
+                    text => {
+                        let played = match text.parse::<Coord>() {
+                            Ok(coord) => driver.play(coord).map(|_| coord).map_err(|err| err.to_string()),
+                            Err(err) => Err(err.to_string()),
+                        };
+                        match played {
+                            Ok(coord) => coord,
+                            Err(message) => {
+                                println!("{}", message);
+                                continue;
+                            }
+                        }
+                    }
+                }
+            }
+        };
+
+        if let Err(err) = connection.send(&NetMessage::Move { coord, hash: driver.game().position_hash() }) {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
+
+    println!();
+    println!("{}", BoardRenderer::new(driver.game(), render_options()));
+    print_status(driver.session());
+}
+
+// A different seed on every run, for anything that should vary between games
+fn time_seed() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_nanos() as u64)
+}
+
+// Puzzles already in the file are kept, and found again only once
+fn make_puzzles(game: &Game, path: &str, from_game: bool) {
+    let mut puzzles = match fs::metadata(path) {
+        Ok(_) => puzzle::load(path).unwrap_or_else(|err| {
+            eprintln!("{}: {}", path, err);
+            process::exit(1);
+        }),
+        Err(_) => Vec::new(),
+    };
+    let existing = puzzles.len();
+
+    let options = PuzzleOptions { rules: game.rules(), ..PuzzleOptions::default() };
+    let found = match from_game {
+        true => puzzle::find_puzzles(game, &options),
+        false => {
+            println!("Playing {} games to find puzzles in...", SELF_PLAY_PUZZLE_GAMES);
+            puzzle::generate(SELF_PLAY_PUZZLE_GAMES, time_seed(), &options)
+        }
+    };
+    for found_puzzle in found {
+        if !puzzle::contains(&puzzles, &found_puzzle) {
+            puzzles.push(found_puzzle);
+        }
+    }
+
+    match puzzle::save(path, &puzzles) {
+        Ok(()) => println!("Added {} puzzles to {}", puzzles.len() - existing, path),
+        Err(err) => {
+            eprintln!("{}: {}", path, err);
+            process::exit(1);
+        }
+    }
+}
+
+fn run_puzzles(path: &str) {
+    let puzzles = puzzle::load(path).unwrap_or_else(|err| {
+        eprintln!("{}: {}", path, err);
+        process::exit(1);
+    });
+
+    println!("Find the only winning move. Type a move, `skip` to see the answer or `quit` to stop.");
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let (mut solved, mut attempted) = (0, 0);
+
+    'puzzles: for (index, puzzle) in puzzles.iter().enumerate() {
+        println!();
+        match puzzle.rules == Rules::standard() {
+            true => println!("Puzzle {} of {}: {} to play and win", index + 1, puzzles.len(), puzzle.player),
+            false => println!("Puzzle {} of {}: {} to play and win ({} rules)", index + 1, puzzles.len(), puzzle.player, puzzle.rules),
+        }
+        println!("{}", BoardRenderer::new(&puzzle.game(), RenderOptions { legal_moves: true, ..render_options() }));
+
+        loop {
+            print!("> ");
+            let _ = io::stdout().flush();
+
+            let line = match lines.next() {
+                Some(Ok(line)) => line,
+                _ => break 'puzzles, // end of input
+            };
+            let answer = match line.trim() {
+                "" => continue,
+                "quit" | "exit" => break 'puzzles,
+                "skip" => None,
+                text => match text.parse::<Coord>() {
+                    Ok(coord) => Some(coord),
+                    Err(err) => {
+                        println!("{}", err);
+                        continue;
+                    }
+                },
+            };
+
+            let result = Score::Exact(puzzle.margin);
+            match answer.map(|coord| (coord, puzzle.check(coord))) {
+                Some((_, Ok(true))) => {
+                    solved += 1;
+                    println!("Correct! {} wins, and only it does ({})", puzzle.solution, result);
+                }
+                Some((coord, Ok(false))) => println!("{} doesn't win. The only winning move is {} ({})", coord, puzzle.solution, result),
+                Some((_, Err(err))) => {
+                    println!("{}", err);
+                    continue;
+                }
+                None => println!("The only winning move is {} ({})", puzzle.solution, result),
+            }
+            attempted += 1;
+            break;
+        }
+    }
+
+    println!();
+    println!("Solved {} of {}", solved, attempted);
+}
+
+#[cfg(feature = "tui")]
+fn run_tui(driver: MatchDriver) {
+    if let Err(err) = tui::run(driver) {
+        eprintln!("Terminal error: {}", err);
+        process::exit(1);
+    }
+}
+
+#[cfg(not(feature = "tui"))]
+fn run_tui(_driver: MatchDriver) {
+    eprintln!("This build doesn't include the terminal interface, enable the `tui` feature to use it");
+    process::exit(2);
+}
+
+// The session, what to do with it, and the number of threads for the engines,
+// including those giving hints and analyzing games
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<(SavedGame, Mode, usize), String> {
+    let mut names: [Option<String>; 2] = [None, None];
+    let mut engines: [Option<Engine>; 2] = [None, None];
+    let mut mode = Mode::Local;
+    let mut clocks: Option<Clocks> = None;
+    let mut load_path: Option<String> = None;
+    let mut threads: Option<usize> = None;
+    let mut rules: Option<Rules> = None;
+    let mut start: Option<Start> = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for {}", arg));
+
+        match arg.as_str() {
+            "--tui" => mode = Mode::Tui,
+            "--load" => load_path = Some(value()?),
+            "--white" => names[0] = Some(value()?),
+            "--black" => names[1] = Some(value()?),
+            "--white-engine" => engines[0] = Some(parse_engine(&value()?)?),
+            "--black-engine" => engines[1] = Some(parse_engine(&value()?)?),
+            "--threads" => {
+                let value = value()?;
+                match value.parse() {
+                    Ok(count) if count > 0 => threads = Some(count),
+                    _ => return Err(format!("Invalid thread count {}", value)),
+                }
+            }
+            "--host" => {
+                let value = value()?;
+                match value.parse() {
+                    Ok(port) => mode = Mode::Host(port),
+                    _ => return Err(format!("Invalid port {}", value)),
+                }
+            }
+            "--join" => mode = Mode::Join(value()?),
+            "--analyze" => mode = Mode::Analyze(parse_engine(&value()?)?),
+            "--make-puzzles" => mode = Mode::MakePuzzles { path: value()?, from_game: false },
+            "--puzzles" => mode = Mode::Puzzles(value()?),
+            "--rules" => rules = Some(value()?.parse().map_err(|err| format!("{}", err))?),
+            "--start" => match value()?.as_str() {
+                "xot" => start = Some(Start::random_xot(&mut Rng::new(time_seed()))),
+                text => start = Some(text.parse().map_err(|err| format!("{}", err))?),
+            },
+            "--clock" => {
+                let control: TimeControl = value()?.parse().map_err(|err| format!("{}", err))?;
+                clocks = Some(Clocks::new(control));
+            }
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+    }
+
+    if let Mode::MakePuzzles { from_game, .. } = &mut mode {
+        *from_game = load_path.is_some();
+    }
+
+    // A saved game keeps its own clocks and rules, but the players can be
+    // renamed or handed over to the engine
+    let mut session = match load_path {
+        Some(path) => {
+            if clocks.is_some() || rules.is_some() || start.is_some() {
+                return Err(String::from("--clock, --rules and --start can't be used with --load, the saved game keeps its own"));
+            }
+            SavedGame::load(&path).map_err(|err| format!("{}: {}", path, err))?
+        }
+        None => {
+            let rules = rules.unwrap_or_default();
+            SavedGame::new(Players::default(), Game::with_rules(start.map_or(rules, |start| rules.with_start(start))), clocks)
+        }
+    };
+    for (index, piece) in [Piece::White, Piece::Black].iter().enumerate() {
+        let player = session.players.get_mut(*piece);
+        if let Some(name) = names[index].take() {
+            player.name = name;
+        }
+        if engines[index].is_some() {
+            player.engine = engines[index];
+        }
+    }
+
+    // The thread count depends on the machine, so it isn't saved with the game
+    if let Some(threads) = threads {
+        for piece in [Piece::White, Piece::Black].iter() {
+            let player = session.players.get_mut(*piece);
+            player.engine = player.engine.map(|engine| engine.with_threads(threads));
+        }
+        if let Mode::Analyze(engine) = mode {
+            mode = Mode::Analyze(engine.with_threads(threads));
+        }
+    }
+
+    // Each side would charge the other's moves with the network delay
+    if matches!(mode, Mode::Host(_) | Mode::Join(_)) && session.clocks.is_some() {
+        return Err(String::from("Clocks can't be used in network games"));
+    }
+
+    Ok((session, mode, threads.unwrap_or(1)))
+}
+
+fn parse_engine(value: &str) -> Result<Engine, String> {
+    match value.parse() {
+        Ok(depth) if depth > 0 => Ok(Engine::new(depth)),
+        _ => Err(format!("Invalid engine depth {}", value)),
+    }
+}
+
+fn print_status(session: &SavedGame) {
+    let game = &session.game;
+    let name_of = |piece: Piece| &session.players.get(piece).name;
 
-    println!("{}", game);
+    let white_count = game.board().count_pieces(Piece::White);
+    let black_count = game.board().count_pieces(Piece::Black);
+    match game.rules().win_condition {
+        WinCondition::MostDiscs => println!("W: {} x B: {}", white_count, black_count),
+        WinCondition::FewestDiscs => println!("W: {} x B: {} (fewer discs win)", white_count, black_count),
+    }
 
-    game.try_play((3, 2).into());
+    let white_stable = game.board().stable_discs(Piece::White).len();
+    let black_stable = game.board().stable_discs(Piece::Black).len();
+    if white_stable + black_stable > 0 {
+        println!("Stable discs: W {} x B {}", white_stable, black_stable);
+    }
 
-    println!("{}", game);
+    if let Some(clocks) = &session.clocks {
+        println!("Clocks: W {} | B {}", clocks.get(Piece::White), clocks.get(Piece::Black));
+    }
 
-    game.try_play((4, 2).into());
+    match game.result() {
+        Some(GameResult::Win(winner)) => println!("Game over! {} ({}) wins", name_of(winner), winner),
+        Some(GameResult::WinOnTime(winner)) => {
+            println!("Game over! {} ({}) ran out of time. {} ({}) wins", name_of(winner.opponent()), winner.opponent(), name_of(winner), winner)
+        }
+        Some(GameResult::Draw) => println!("Game over! It's a draw"),
+        None => {
+            if game.state() == GameState::PlayedAndPassed {
+                println!("{} had no valid moves and passed", name_of(game.current_turn().player.opponent()));
+            }
 
-    println!("{}", game);
+            let valid_moves: Vec<String> = game.current_turn().valid_moves.iter().map(|mv| mv.coord().to_string()).collect();
+            println!(
+                "{} ({}) to play. Valid moves: {}",
+                name_of(game.current_turn().player),
+                game.current_turn().player,
+                valid_moves.join(" ")
+            );
+        }
+    }
 }
@@ -0,0 +1,371 @@
+// Python bindings, built into a `reversi_rust` extension module with maturin.
+// Coords can be given as `Coord` objects or in the usual notation (like "d3"),
+// and players are "W" or "B".
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::reversi::board::Board;
+use crate::reversi::coord::Coord;
+use crate::reversi::engine::{Engine, SearchLimits};
+use crate::reversi::game::{Game, GameResult};
+use crate::reversi::piece::{BoardSquare, Piece};
+use crate::reversi::planes::{self, Planes};
+use crate::reversi::rules::{ParseRulesError, Rules};
+
+create_exception!(reversi_rust, PlayError, PyValueError, "Raised when a move can't be played.");
+
+fn parse_piece(text: &str) -> PyResult<Piece> {
+    match text.to_ascii_lowercase().as_str() {
+        "w" | "white" => Ok(Piece::White),
+        "b" | "black" => Ok(Piece::Black),
+        _ => Err(PyValueError::new_err(format!("Invalid player {}, expected W or B", text))),
+    }
+}
+
+#[pyclass(name = "Coord", module = "reversi_rust", frozen, eq, hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Hash)]
+struct PyCoord {
+    coord: Coord,
+}
+
+#[pymethods]
+impl PyCoord {
+    #[new]
+    fn new(row: usize, col: usize) -> PyResult<PyCoord> {
+        if row >= Board::BOARD_SIZE || col >= Board::BOARD_SIZE {
+            return Err(PyValueError::new_err(format!("Coord({}, {}) is off the board, rows and columns go from 0 to {}", row, col, Board::BOARD_SIZE - 1)));
+        }
+        Ok(PyCoord { coord: Coord::from((row, col)) })
+    }
+
+    #[staticmethod]
+    fn parse(text: &str) -> PyResult<PyCoord> {
+        text.parse().map(|coord| PyCoord { coord }).map_err(|err| PyValueError::new_err(format!("{}", err)))
+    }
+
+    #[getter]
+    fn row(&self) -> usize {
+        self.coord.row
+    }
+
+    #[getter]
+    fn col(&self) -> usize {
+        self.coord.col
+    }
+
+    fn __str__(&self) -> String {
+        self.coord.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Coord({}, {})", self.coord.row, self.coord.col)
+    }
+}
+
+#[derive(FromPyObject)]
+enum CoordArg {
+    Coord(PyCoord),
+    Notation(String),
+}
+
+impl CoordArg {
+    fn coord(self) -> PyResult<Coord> {
+        match self {
+            CoordArg::Coord(coord) => Ok(coord.coord),
+            CoordArg::Notation(text) => PyCoord::parse(&text).map(|coord| coord.coord),
+        }
+    }
+}
+
+#[pyclass(name = "Board", module = "reversi_rust", eq)]
+#[derive(Clone, Debug, PartialEq)]
+struct PyBoard {
+    board: Board,
+}
+
+#[pymethods]
+impl PyBoard {
+    // The standard starting position
+    #[new]
+    fn new() -> PyBoard {
+        PyBoard { board: Board::new() }
+    }
+
+    #[staticmethod]
+    fn empty() -> PyBoard {
+        PyBoard { board: Board::empty() }
+    }
+
+    // The player with a disc on the square, or None when it's empty
+    fn get(&self, coord: CoordArg) -> PyResult<Option<String>> {
+        match self.board.get_coord_square_at(coord.coord()?) {
+            (_, BoardSquare::Played(piece)) => Ok(Some(piece.to_string())),
+            (_, BoardSquare::Unplayed) => Ok(None),
+            (coord, BoardSquare::OutOfBounds) => Err(PyValueError::new_err(format!("{} is out of bounds", coord))),
+        }
+    }
+
+    fn set(&mut self, coord: CoordArg, player: &str) -> PyResult<()> {
+        let coord = coord.coord()?;
+        if !self.board.is_in_bounds(&coord) {
+            return Err(PyValueError::new_err(format!("{} is out of bounds", coord)));
+        }
+
+        self.board.set_squares(&vec![coord], parse_piece(player)?);
+        Ok(())
+    }
+
+    fn count(&self, player: &str) -> PyResult<usize> {
+        Ok(self.board.count_pieces(parse_piece(player)?))
+    }
+
+    // Nested lists of 2 planes of 8 rows of 8 columns, ready for numpy.array:
+    // the player's discs, then their opponent's
+    fn planes(&self, player: &str) -> PyResult<Planes> {
+        Ok(planes::encode(&self.board, parse_piece(player)?))
+    }
+
+    fn copy(&self) -> PyBoard {
+        self.clone()
+    }
+
+    fn __copy__(&self) -> PyBoard {
+        self.clone()
+    }
+
+    fn __deepcopy__(&self, _memo: &Bound<'_, PyAny>) -> PyBoard {
+        self.clone()
+    }
+
+    fn __str__(&self) -> String {
+        self.board.to_string()
+    }
+}
+
+#[pyclass(name = "Game", module = "reversi_rust")]
+#[derive(Clone)]
+struct PyGame {
+    game: Game,
+}
+
+#[pymethods]
+impl PyGame {
+    // A new game, or a game from the given position and player to move, with
+    // "standard" or "misere" rules
+    #[new]
+    #[pyo3(signature = (board = None, player = "W", rules = "standard"))]
+    fn new(board: Option<PyBoard>, player: &str, rules: &str) -> PyResult<PyGame> {
+        let rules: Rules = rules.parse().map_err(|err: ParseRulesError| PyValueError::new_err(format!("{}", err)))?;
+        let game = match board {
+            Some(board) => Game::from_position_with_rules(board.board, parse_piece(player)?, rules),
+            None => Game::with_rules(rules),
+        };
+
+        Ok(PyGame { game })
+    }
+
+    #[getter]
+    fn board(&self) -> PyBoard {
+        PyBoard { board: *self.game.board() }
+    }
+
+    #[getter]
+    fn current_player(&self) -> String {
+        self.game.current_turn().player.to_string()
+    }
+
+    // One of "New", "Played", "PlayedAndPassed" or "GameOver"
+    #[getter]
+    fn state(&self) -> String {
+        format!("{:?}", self.game.state())
+    }
+
+    // The winner, "draw", or None while the game is still on
+    #[getter]
+    fn result(&self) -> Option<String> {
+        match self.game.result()? {
+            GameResult::Win(winner) | GameResult::WinOnTime(winner) => Some(winner.to_string()),
+            GameResult::Draw => Some(String::from("draw")),
+        }
+    }
+
+    #[getter]
+    fn history(&self) -> Vec<PyCoord> {
+        self.game.history().iter().map(|coord| PyCoord { coord: *coord }).collect()
+    }
+
+    #[getter]
+    fn position_hash(&self) -> u64 {
+        self.game.position_hash()
+    }
+
+    fn is_over(&self) -> bool {
+        self.game.result().is_some()
+    }
+
+    fn legal_moves(&self) -> Vec<PyCoord> {
+        match self.game.result() {
+            Some(_) => Vec::new(),
+            None => self.game.current_turn().valid_moves.iter().map(|mv| PyCoord { coord: *mv.coord() }).collect(),
+        }
+    }
+
+    fn try_play(&mut self, coord: CoordArg) -> PyResult<()> {
+        self.game.try_play(coord.coord()?).map_err(|err| PlayError::new_err(err.to_string()))
+    }
+
+    // Takes back the last move, returning it
+    fn undo(&mut self) -> Option<PyCoord> {
+        self.game.undo().map(|coord| PyCoord { coord })
+    }
+
+    // The board's planes from the point of view of the player to move
+    fn planes(&self) -> Planes {
+        planes::encode(self.game.board(), self.game.current_turn().player)
+    }
+
+    fn copy(&self) -> PyGame {
+        self.clone()
+    }
+
+    fn __copy__(&self) -> PyGame {
+        self.clone()
+    }
+
+    fn __deepcopy__(&self, _memo: &Bound<'_, PyAny>) -> PyGame {
+        self.clone()
+    }
+
+    fn __str__(&self) -> String {
+        self.game.board().to_string()
+    }
+}
+
+#[pyclass(name = "Engine", module = "reversi_rust", frozen)]
+struct PyEngine {
+    engine: Engine,
+}
+
+// Searches run without holding the GIL, so other Python threads can go on
+#[pymethods]
+impl PyEngine {
+    #[new]
+    #[pyo3(signature = (depth = Engine::DEFAULT_DEPTH, threads = 1))]
+    fn new(depth: u32, threads: usize) -> PyEngine {
+        PyEngine { engine: Engine::new(depth.max(1)).with_threads(threads.max(1)) }
+    }
+
+    #[getter]
+    fn depth(&self) -> u32 {
+        self.engine.depth()
+    }
+
+    fn choose_move(&self, py: Python<'_>, game: &PyGame) -> Option<PyCoord> {
+        let (engine, game) = (self.engine, game.game.clone());
+        py.detach(|| engine.choose_move(&game, None)).map(|coord| PyCoord { coord })
+    }
+
+    // The best move, its score for the player to move, the depth reached and
+    // the number of positions searched
+    fn search(&self, py: Python<'_>, game: &PyGame) -> (Option<PyCoord>, i32, u32, u64) {
+        let (engine, game) = (self.engine, game.game.clone());
+        let limits = SearchLimits { max_depth: engine.depth(), ..SearchLimits::default() };
+
+        let result = py.detach(|| engine.search(&game, &limits));
+        (result.best_move.map(|coord| PyCoord { coord }), result.score, result.depth, result.nodes)
+    }
+
+    // The static evaluation of the position for the given player
+    #[staticmethod]
+    fn evaluate(game: &PyGame, player: &str) -> PyResult<i32> {
+        Ok(Engine::evaluate(&game.game, parse_piece(player)?))
+    }
+}
+
+#[pymodule]
+fn reversi_rust(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyCoord>()?;
+    module.add_class::<PyBoard>()?;
+    module.add_class::<PyGame>()?;
+    module.add_class::<PyEngine>()?;
+    module.add("PlayError", module.py().get_type::<PlayError>())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::prelude::*;
+
+    use super::{CoordArg, PlayError, PyBoard, PyCoord, PyEngine, PyGame};
+
+    fn notation(text: &str) -> CoordArg {
+        CoordArg::Notation(text.to_string())
+    }
+
+    #[test]
+    fn games_are_played_with_coords_or_notation() {
+        let mut game = PyGame::new(None, "W", "standard").unwrap();
+        assert_eq!(game.legal_moves().len(), 4);
+
+        game.try_play(CoordArg::Coord(PyCoord::new(2, 3).unwrap())).unwrap();
+        game.try_play(notation("c5")).unwrap();
+
+        assert_eq!(game.history(), vec![PyCoord::new(2, 3).unwrap(), PyCoord::parse("c5").unwrap()]);
+        assert!(PyCoord::new(8, 0).is_err());
+        assert!(PyCoord::new(0, 300).is_err());
+        assert_eq!(game.current_player(), "W");
+        assert_eq!(game.state(), "Played");
+        assert_eq!(game.undo(), Some(PyCoord::parse("c5").unwrap()));
+    }
+
+    #[test]
+    fn invalid_moves_raise_play_error() {
+        Python::initialize();
+        let mut game = PyGame::new(None, "W", "standard").unwrap();
+
+        let err = game.try_play(notation("a1")).unwrap_err();
+
+        Python::attach(|py| assert!(err.is_instance_of::<PlayError>(py)));
+        assert!(game.try_play(notation("zz")).is_err());
+    }
+
+    #[test]
+    fn copies_are_independent() {
+        let game = PyGame::new(None, "W", "standard").unwrap();
+        let mut copy = game.copy();
+
+        copy.try_play(notation("d3")).unwrap();
+
+        assert!(game.history().is_empty());
+        assert_ne!(game.board(), copy.board());
+    }
+
+    #[test]
+    fn games_start_from_custom_boards() {
+        let mut board = PyBoard::empty();
+        board.set(notation("a1"), "B").unwrap();
+        board.set(notation("b1"), "W").unwrap();
+        assert!(board.set(notation("i9"), "B").is_err());
+
+        let game = PyGame::new(Some(board), "B", "standard").unwrap();
+
+        assert_eq!(game.legal_moves(), vec![PyCoord::parse("c1").unwrap()]);
+        assert_eq!(game.board().get(notation("a1")).unwrap(), Some(String::from("B")));
+        assert_eq!(game.planes()[0][0][0], 1.0);
+    }
+
+    #[test]
+    fn engine_searches_without_the_gil() {
+        Python::initialize();
+        let game = PyGame::new(None, "W", "standard").unwrap();
+        let engine = PyEngine::new(3, 2);
+
+        let (best_move, _, depth, nodes) = Python::attach(|py| engine.search(py, &game));
+
+        assert!(game.legal_moves().contains(&best_move.unwrap()));
+        assert_eq!(depth, 3);
+        assert!(nodes > 0);
+    }
+}
@@ -0,0 +1,296 @@
+use std::fmt;
+
+use crate::reversi::coord::Coord;
+use crate::reversi::engine::{Engine, SearchLimits};
+use crate::reversi::game::{Game, GameState};
+use crate::reversi::piece::{BoardSquare, Piece};
+
+// Score losses, in the engine's units, from which a move is flagged
+const INACCURACY_LOSS: i32 = 30;
+const MISTAKE_LOSS: i32 = 80;
+const BLUNDER_LOSS: i32 = 200;
+
+// The same, in discs, for positions that were solved exactly
+const INACCURACY_DISCS: i32 = 2;
+const MISTAKE_DISCS: i32 = 6;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnalysisOptions {
+    pub engine: Engine,
+    pub exact_empties: usize, // solve positions exactly from this many empty squares
+}
+
+impl Default for AnalysisOptions {
+    fn default() -> Self {
+        AnalysisOptions { engine: Engine::default(), exact_empties: 10 }
+    }
+}
+
+// How good a position is for the player to move
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Score {
+    Estimate(i32), // the engine's evaluation
+    Exact(i32),    // the final disc difference with perfect play
+}
+
+impl Score {
+    // A search score, which is exact once the search has seen the game through
+    pub fn from_search(score: i32) -> Score {
+        match Engine::is_decided(score) {
+            true => Score::Exact(Engine::disc_difference(score)),
+            false => Score::Estimate(score),
+        }
+    }
+
+    fn value(&self) -> i32 {
+        match self {
+            Score::Estimate(value) | Score::Exact(value) => *value,
+        }
+    }
+
+    fn negate(&self) -> Score {
+        match self {
+            Score::Estimate(value) => Score::Estimate(-value),
+            Score::Exact(value) => Score::Exact(-value),
+        }
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Score::Estimate(value) => write!(f, "{:+}", value),
+            Score::Exact(0) => write!(f, "draw"),
+            Score::Exact(value) if *value > 0 => write!(f, "win by {}", value),
+            Score::Exact(value) => write!(f, "loss by {}", -value),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum Judgement {
+    Good,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl fmt::Display for Judgement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Judgement::Good => write!(f, "good"),
+            Judgement::Inaccuracy => write!(f, "inaccuracy"),
+            Judgement::Mistake => write!(f, "mistake"),
+            Judgement::Blunder => write!(f, "blunder"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoveAnalysis {
+    pub player: Piece,
+    pub played: Coord,
+    pub best: Coord,
+    pub played_score: Score, // both for the player who moved
+    pub best_score: Score,
+    pub judgement: Judgement,
+}
+
+impl MoveAnalysis {
+    // How much worse the played move was than the best one, in the scores'
+    // unit, or 0 when one score is exact and the other isn't, since discs and
+    // estimates can't be compared
+    pub fn loss(&self) -> i32 {
+        match (self.best_score, self.played_score) {
+            (Score::Exact(best), Score::Exact(played)) | (Score::Estimate(best), Score::Estimate(played)) => (best - played).max(0),
+            _ => 0,
+        }
+    }
+
+    fn judge(&self) -> Judgement {
+        let loss = self.loss();
+
+        match (self.best_score, self.played_score) {
+            // Throwing away a win or a draw is a blunder, however few discs it costs
+            (Score::Exact(best), played) if best.signum() > played.value().signum() => Judgement::Blunder,
+            // and so is walking into a lost ending when the best move isn't known to lose
+            (Score::Estimate(_), Score::Exact(played)) if played < 0 => Judgement::Blunder,
+            (Score::Exact(_), Score::Exact(_)) if loss >= MISTAKE_DISCS => Judgement::Mistake,
+            (Score::Exact(_), Score::Exact(_)) if loss >= INACCURACY_DISCS => Judgement::Inaccuracy,
+            (Score::Estimate(_), Score::Estimate(_)) if loss >= BLUNDER_LOSS => Judgement::Blunder,
+            (Score::Estimate(_), Score::Estimate(_)) if loss >= MISTAKE_LOSS => Judgement::Mistake,
+            (Score::Estimate(_), Score::Estimate(_)) if loss >= INACCURACY_LOSS => Judgement::Inaccuracy,
+            _ => Judgement::Good,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Analysis {
+    pub moves: Vec<MoveAnalysis>,
+}
+
+impl Analysis {
+    pub fn count(&self, player: Piece, judgement: Judgement) -> usize {
+        self.moves.iter().filter(|mv| mv.player == player && mv.judgement == judgement).count()
+    }
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, mv) in self.moves.iter().enumerate() {
+            write!(f, "{:>3}. {} {}", index + 1, mv.player, mv.played)?;
+
+            match mv.played == mv.best {
+                true => writeln!(f, "  best move ({})", mv.best_score)?,
+                false if mv.judgement == Judgement::Good => writeln!(f, "  {} (best {} {})", mv.played_score, mv.best, mv.best_score)?,
+                false if mv.loss() == 0 => writeln!(f, "  {} (best {} {}) {}", mv.played_score, mv.best, mv.best_score, mv.judgement)?,
+                false => writeln!(f, "  {} (best {} {}) {}, losing {}", mv.played_score, mv.best, mv.best_score, mv.judgement, mv.loss())?,
+            }
+        }
+
+        for piece in [Piece::White, Piece::Black].iter() {
+            writeln!(
+                f,
+                "{}: {} inaccuracies, {} mistakes, {} blunders",
+                piece,
+                self.count(*piece, Judgement::Inaccuracy),
+                self.count(*piece, Judgement::Mistake),
+                self.count(*piece, Judgement::Blunder)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+// Replays a game from its initial position and compares every move played
+// with the engine's choice. Positions with few empty squares are solved
+// exactly, and the others are searched to the engine's depth.
+pub fn analyze(game: &Game, options: &AnalysisOptions) -> Analysis {
+    let mut position = game.restart();
+    let mut analysis = Analysis::default();
+
+    for played in game.history() {
+        let player = position.current_turn().player;
+        let exact = position.board().count_squares(BoardSquare::Unplayed) <= options.exact_empties;
+        let (best, best_score) = evaluate(&position, options.engine.depth(), exact, options);
+
+        let mut child = position.clone();
+        child.try_play(*played).expect("game history contains only valid moves");
+
+        let played_score = match best == Some(*played) {
+            true => best_score,
+            false => {
+                let (_, score) = evaluate(&child, options.engine.depth().saturating_sub(1).max(1), exact, options);
+                match child.current_turn().player == player {
+                    true => score,
+                    false => score.negate(),
+                }
+            }
+        };
+
+        let mut mv = MoveAnalysis {
+            player,
+            played: *played,
+            best: best.unwrap_or(*played),
+            played_score,
+            best_score,
+            judgement: Judgement::Good,
+        };
+        mv.judgement = mv.judge();
+        analysis.moves.push(mv);
+
+        position = child;
+    }
+
+    analysis
+}
+
+// The best move and the score of a position for its current player, who is
+// the last to have moved once the game is over
+fn evaluate(game: &Game, depth: u32, exact: bool, options: &AnalysisOptions) -> (Option<Coord>, Score) {
+    if exact {
+        let (best, discs) = options.engine.solve(game);
+        return (best, Score::Exact(discs));
+    }
+
+    // Searches that see the end of the game give exact scores too
+    if game.state() == GameState::GameOver {
+        return (None, Score::from_search(Engine::evaluate(game, game.current_turn().player)));
+    }
+
+    let result = options.engine.search(game, &SearchLimits { max_depth: depth, ..SearchLimits::default() });
+    (result.best_move, Score::from_search(result.score))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{analyze, AnalysisOptions, Judgement, MoveAnalysis, Score};
+    use crate::reversi::coord::Coord;
+    use crate::reversi::engine::Engine;
+    use crate::reversi::game::Game;
+    use crate::reversi::piece::{BoardSquare, Piece};
+
+    #[test]
+    fn engine_moves_are_not_flagged() {
+        let mut game = Game::new();
+        let engine = Engine::new(2);
+        for _ in 0..6 {
+            let coord = engine.choose_move(&game, None).unwrap();
+            game.try_play(coord).unwrap();
+        }
+
+        let analysis = analyze(&game, &AnalysisOptions { engine, exact_empties: 0 });
+
+        assert_eq!(analysis.moves.len(), 6);
+        assert!(analysis.moves.iter().all(|mv| mv.played == mv.best && mv.judgement == Judgement::Good));
+        assert!(analysis.to_string().contains("W: 0 inaccuracies, 0 mistakes, 0 blunders"));
+    }
+
+    #[test]
+    fn endgame_mistakes_are_measured_in_discs() {
+        // Play quickly to an endgame, then make the worst move there
+        let engine = Engine::new(1);
+        let mut game = Game::new();
+        while game.board().count_squares(BoardSquare::Unplayed) > 6 {
+            game.try_play(engine.choose_move(&game, None).unwrap()).unwrap();
+        }
+
+        let player = game.current_turn().player;
+        let outcome = |coord: Coord| {
+            let mut child = game.clone();
+            child.try_play(coord).unwrap();
+            let (_, discs) = engine.solve(&child);
+            if child.current_turn().player == player { discs } else { -discs }
+        };
+        let moves: Vec<Coord> = game.current_turn().valid_moves.iter().map(|mv| *mv.coord()).collect();
+        let worst = *moves.iter().min_by_key(|coord| outcome(**coord)).unwrap();
+        let (best, worst_outcome) = (moves.iter().map(|coord| outcome(*coord)).max().unwrap(), outcome(worst));
+        game.try_play(worst).unwrap();
+
+        let analysis = analyze(&game, &AnalysisOptions { engine, exact_empties: 6 });
+        let mv = analysis.moves.last().unwrap();
+
+        assert_eq!(mv.best_score, Score::Exact(best));
+        assert_eq!(mv.played_score, Score::Exact(worst_outcome));
+        assert!(analysis.moves[..analysis.moves.len() - 1].iter().all(|mv| matches!(mv.best_score, Score::Estimate(_))));
+    }
+
+    #[test]
+    fn throwing_away_a_win_is_a_blunder() {
+        let judge = |best_score, played_score| {
+            let mv = MoveAnalysis { player: Piece::White, played: (0, 0).into(), best: (0, 1).into(), played_score, best_score, judgement: Judgement::Good };
+            mv.judge()
+        };
+
+        assert_eq!(judge(Score::Exact(2), Score::Exact(-2)), Judgement::Blunder);
+        assert_eq!(judge(Score::Exact(0), Score::Exact(-2)), Judgement::Blunder);
+        assert_eq!(judge(Score::Exact(20), Score::Exact(12)), Judgement::Mistake);
+        assert_eq!(judge(Score::Exact(-2), Score::Exact(-4)), Judgement::Inaccuracy);
+        assert_eq!(judge(Score::Estimate(50), Score::Estimate(-200)), Judgement::Blunder);
+        assert_eq!(judge(Score::Estimate(50), Score::Estimate(40)), Judgement::Good);
+        assert_eq!(judge(Score::Estimate(50), Score::Exact(-2)), Judgement::Blunder);
+        assert_eq!(judge(Score::Exact(4), Score::Estimate(900)), Judgement::Good);
+    }
+}
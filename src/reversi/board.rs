@@ -1,30 +1,90 @@
 use std::fmt;
-use array2d::Array2D;
 
 use crate::reversi::coord::{Coord, Vector};
 use crate::reversi::piece::{BoardSquare, Piece};
 
+// Zobrist keys for a black or white piece on each square, generated at compile
+// time with splitmix64 so hashes are the same on every run and platform
+const ZOBRIST_KEYS: [[u64; 2]; Board::BOARD_SIZE * Board::BOARD_SIZE] = generate_zobrist_keys();
+
+const fn generate_zobrist_keys() -> [[u64; 2]; Board::BOARD_SIZE * Board::BOARD_SIZE] {
+    let mut keys = [[0; 2]; Board::BOARD_SIZE * Board::BOARD_SIZE];
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut i = 0;
+
+    while i < keys.len() * 2 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        keys[i / 2][i % 2] = z ^ (z >> 31);
+        i += 1;
+    }
+
+    keys
+}
+
+// The squares of each color are kept as bitboards, one bit per square row by
+// row from a1, which makes boards small enough to copy freely
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Board {
-    squares: Array2D<BoardSquare>,
+    black: u64,
+    white: u64,
 }
 
 impl Board {
-    pub const BOARD_SIZE: usize = 8; // keep this low to avoid problems with isize <-> conversions
+    pub const BOARD_SIZE: usize = 8; // must stay 8, for a bitboard to hold every square
+
+    // The lines of the text board, shared with other text renderings
+    pub(crate) const HEADER:      &'static str = "   | A | B | C | D | E | F | G | H |";
+    pub(crate) const ROW_DIVIDER: &'static str = "---+---+---+---+---+---+---+---+---+";
 
     pub fn new() -> Board {
-        let mut board = Board {
-            squares: Array2D::filled_with(BoardSquare::Unplayed, Board::BOARD_SIZE, Board::BOARD_SIZE), };
+        let mut board = Board::empty();
 
-        board.squares[(3, 3)] = BoardSquare::Played(Piece::Black);
-        board.squares[(4, 4)] = BoardSquare::Played(Piece::Black);
-        board.squares[(3, 4)] = BoardSquare::Played(Piece::White);
-        board.squares[(4, 3)] = BoardSquare::Played(Piece::White);
+        board.set_squares(&vec![(3, 3).into(), (4, 4).into()], Piece::Black);
+        board.set_squares(&vec![(3, 4).into(), (4, 3).into()], Piece::White);
 
         board
     }
 
+    pub fn empty() -> Board {
+        Board { black: 0, white: 0 }
+    }
+
     pub fn is_in_bounds(&self, coord: &Coord) -> bool {
-        coord.row < self.squares.num_rows() && coord.col < self.squares.num_columns()
+        coord.row < Board::BOARD_SIZE && coord.col < Board::BOARD_SIZE
+    }
+
+    fn bit(row: usize, col: usize) -> u64 {
+        debug_assert!(row < Board::BOARD_SIZE && col < Board::BOARD_SIZE, "({}, {}) is off the board", row, col);
+        1 << (row * Board::BOARD_SIZE + col)
+    }
+
+    fn square(&self, row: usize, col: usize) -> BoardSquare {
+        let bit = Board::bit(row, col);
+        if self.black & bit != 0 {
+            BoardSquare::Played(Piece::Black)
+        } else if self.white & bit != 0 {
+            BoardSquare::Played(Piece::White)
+        } else {
+            BoardSquare::Unplayed
+        }
+    }
+
+    // Every square's coord, row by row from a1, in the order of the bits
+    pub fn coords() -> impl Iterator<Item = Coord> {
+        (0..Board::BOARD_SIZE * Board::BOARD_SIZE).map(Board::coord_at)
+    }
+
+    // The coord of a square by its index in the order of `coords`
+    pub fn coord_at(index: usize) -> Coord {
+        Coord::from((index / Board::BOARD_SIZE, index % Board::BOARD_SIZE))
+    }
+
+    // The squares row by row
+    fn rows(&self) -> impl Iterator<Item = Vec<BoardSquare>> + '_ {
+        (0..Board::BOARD_SIZE).map(move |row| (0..Board::BOARD_SIZE).map(|col| self.square(row, col)).collect())
     }
 
     pub fn get_coord_square_at(&self, coord: Coord) -> (Coord, BoardSquare) {
@@ -34,32 +94,120 @@ impl Board {
     // This method will fail catastrophically for big board sizes (isize::MAX+1)!
     pub fn get_coord_square_towards(&self, coord: Coord, vector: Vector, hops: usize) -> (Coord, BoardSquare) {
         match coord.towards(&vector, hops) {
-            Some(dest) if dest.row < self.squares.num_rows() && dest.col < self.squares.num_columns() => {
-                (dest, self.squares[(dest.row, dest.col)])
-            }
+            Some(dest) if self.is_in_bounds(&dest) => (dest, self.square(dest.row, dest.col)),
             _ => (coord, BoardSquare::OutOfBounds),
         }
     }
 
+    // Zobrist hash of the pieces on the board, which is the same for equal
+    // boards and very unlikely to be the same for different ones
+    pub fn hash(&self) -> u64 {
+        ZOBRIST_KEYS
+            .iter()
+            .enumerate()
+            .fold(0, |hash, (index, keys)| match (self.black >> index & 1, self.white >> index & 1) {
+                (1, _) => hash ^ keys[0],
+                (_, 1) => hash ^ keys[1],
+                _ => hash,
+            })
+    }
+
+    pub fn count_pieces(&self, piece: Piece) -> usize {
+        self.count_squares(BoardSquare::Played(piece))
+    }
+
+    pub fn count_squares(&self, square: BoardSquare) -> usize {
+        let count = match square {
+            BoardSquare::Played(Piece::Black) => self.black.count_ones(),
+            BoardSquare::Played(Piece::White) => self.white.count_ones(),
+            BoardSquare::Unplayed => (!(self.black | self.white)).count_ones(),
+            BoardSquare::OutOfBounds => 0,
+        };
+        count as usize
+    }
+
     // This is a naive method that could put the board in an invalid state,
     // which is okay because it optimizes the code by avoiding roundtrips and
-    // makes it so that the Board doesn't know the rules of the game
+    // makes it so that the Board doesn't know the rules of the game. Coords off
+    // the board panic, before any square is set, rather than wrapping around.
     pub fn set_squares(&mut self, coords: &Vec<Coord>, player: Piece) {
+        if let Some(coord) = coords.iter().find(|coord| !self.is_in_bounds(coord)) {
+            panic!("({}, {}) is off the board", coord.row, coord.col);
+        }
+
         for coord in coords {
-            self.squares[(coord.row, coord.col)] = BoardSquare::Played(player);
+            let bit = Board::bit(coord.row, coord.col);
+            match player {
+                Piece::Black => { self.black |= bit; self.white &= !bit; }
+                Piece::White => { self.white |= bit; self.black &= !bit; }
+            }
         }
     }
+
+    // The player's discs that can never be flipped again, row by row. This is
+    // conservative: a disc counts as stable when, along each of the four lines
+    // through it, the line is full or the disc is backed by the edge or by a
+    // stable disc of its own color, which misses a few rarer stable discs.
+    pub fn stable_discs(&self, piece: Piece) -> Vec<Coord> {
+        const AXES: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+        let mut stable: u64 = 0;
+
+        // Whether the square next to a disc, going along a vector, backs it up
+        let backed = |stable: u64, coord: Coord, vector: Vector| match self.get_coord_square_towards(coord, vector, 1) {
+            (_, BoardSquare::OutOfBounds) => true,
+            (next, BoardSquare::Played(next_piece)) => next_piece == piece && stable & Board::bit(next.row, next.col) != 0,
+            (_, BoardSquare::Unplayed) => false,
+        };
+
+        let line_is_full = |coord: Coord, vector: Vector| {
+            let backwards = Vector::from((-vector.row, -vector.col));
+            [vector, backwards].iter().all(|vector| {
+                (1..Board::BOARD_SIZE).map(|hops| self.get_coord_square_towards(coord, *vector, hops).1)
+                    .take_while(|square| *square != BoardSquare::OutOfBounds)
+                    .all(|square| square != BoardSquare::Unplayed)
+            })
+        };
+
+        // Stability spreads from the edges and corners, so repeat until no more
+        // discs become stable
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for row in 0..Board::BOARD_SIZE {
+                for col in 0..Board::BOARD_SIZE {
+                    let coord = Coord::from((row, col));
+                    if stable & Board::bit(row, col) != 0 || self.square(row, col) != BoardSquare::Played(piece) { continue; }
+
+                    let is_stable = AXES.iter().all(|axis| {
+                        let vector = Vector::from(*axis);
+                        backed(stable, coord, vector) || backed(stable, coord, Vector::from((-axis.0, -axis.1))) || line_is_full(coord, vector)
+                    });
+
+                    if is_stable {
+                        stable |= Board::bit(row, col);
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        Board::coords().filter(|coord| stable & Board::bit(coord.row, coord.col) != 0).collect()
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Board::new()
+    }
 }
 
 impl fmt::Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        const HEADER:      &str = "   | A | B | C | D | E | F | G | H |";
-        const ROW_DIVIDER: &str = "---+---+---+---+---+---+---+---+---+";
+        writeln!(f, "{}", Board::HEADER)?;
+        writeln!(f, "{}", Board::ROW_DIVIDER)?;
 
-        writeln!(f, "{}", HEADER)?;
-        writeln!(f, "{}", ROW_DIVIDER)?;
-
-        for (i, positions_in_row) in self.squares.rows_iter().enumerate() {
+        for (i, positions_in_row) in self.rows().enumerate() {
             write!(f, " {} |", i+1)?;
 
             for position in positions_in_row {
@@ -67,15 +215,53 @@ impl fmt::Display for Board {
             }
 
             writeln!(f)?;
-            writeln!(f, "{}", ROW_DIVIDER)?;
+            writeln!(f, "{}", Board::ROW_DIVIDER)?;
         }
 
         Ok(()) // if you got here, it means there were no errors
     }
 }
 
+// Boards are serialized as a list of rows, which reads naturally in JSON and
+// lets the deserializer reject anything that isn't a full 8x8 grid
+#[cfg(feature = "serde")]
+impl serde::Serialize for Board {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.rows())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Board {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Board, D::Error> {
+        use serde::de::Error;
+
+        let rows: Vec<Vec<BoardSquare>> = serde::Deserialize::deserialize(deserializer)?;
+
+        if rows.len() != Board::BOARD_SIZE || rows.iter().any(|row| row.len() != Board::BOARD_SIZE) {
+            return Err(D::Error::custom(format!("board must be {0}x{0} squares", Board::BOARD_SIZE)));
+        }
+
+        if rows.iter().flatten().any(|square| *square == BoardSquare::OutOfBounds) {
+            return Err(D::Error::custom("board cannot contain out of bounds squares"));
+        }
+
+        let mut board = Board::empty();
+        for (row, squares) in rows.iter().enumerate() {
+            for (col, square) in squares.iter().enumerate() {
+                if let BoardSquare::Played(piece) = square {
+                    board.set_squares(&vec![(row, col).into()], *piece);
+                }
+            }
+        }
+        Ok(board)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+
     use super::{Board, Piece, BoardSquare};
     use crate::reversi::coord::Coord;
 
@@ -84,14 +270,14 @@ mod tests {
         let board = Board::new();
 
         // Asserting initial positions
-        assert_eq!(board.squares[(3, 3)], BoardSquare::Played(Piece::Black));
-        assert_eq!(board.squares[(4, 4)], BoardSquare::Played(Piece::Black));
-        assert_eq!(board.squares[(3, 4)], BoardSquare::Played(Piece::White));
-        assert_eq!(board.squares[(4, 3)], BoardSquare::Played(Piece::White));
+        assert_eq!(board.square(3, 3), BoardSquare::Played(Piece::Black));
+        assert_eq!(board.square(4, 4), BoardSquare::Played(Piece::Black));
+        assert_eq!(board.square(3, 4), BoardSquare::Played(Piece::White));
+        assert_eq!(board.square(4, 3), BoardSquare::Played(Piece::White));
 
-        // Asserting board size, which must be 8 to avoid problems with isize <-> conversions
-        assert_eq!(board.squares.num_columns(), 8);
-        assert_eq!(board.squares.num_rows(), 8);
+        // Asserting board size, which must be 8 for every square to fit in a bitboard
+        assert_eq!(board.count_squares(BoardSquare::Unplayed), 60);
+        assert_eq!(board.rows().count(), 8);
     }
 
     #[test]
@@ -103,8 +289,9 @@ mod tests {
 
         board.set_squares(coords, Piece::White);
 
-        assert_eq!(board.squares[(3,2)], BoardSquare::Played(Piece::White));
-        assert_eq!(board.squares[(3,3)], BoardSquare::Played(Piece::White));
+        assert_eq!(board.square(3, 2), BoardSquare::Played(Piece::White));
+        assert_eq!(board.square(3, 3), BoardSquare::Played(Piece::White));
+        assert_eq!(board.count_pieces(Piece::Black), 1);
     }
 
     #[test]
@@ -121,4 +308,80 @@ mod tests {
         assert!(!board.is_in_bounds(&(0, 8).into())); // one past last column
         assert!(!board.is_in_bounds(&(9, 9).into())); // well beyond
     }
+
+    #[test]
+    fn off_board_coords_are_never_set() {
+        for coords in [vec![(0, 9).into()], vec![(2, 3).into(), (8, 0).into()]] {
+            let mut board = Board::new();
+
+            let result = panic::catch_unwind(AssertUnwindSafe(|| board.set_squares(&coords, Piece::White)));
+
+            assert!(result.is_err());
+            assert_eq!(board, Board::new());
+        }
+    }
+
+    #[test]
+    fn equal_boards_have_equal_hashes() {
+        let mut board = Board::new();
+        let initial_hash = board.hash();
+        assert_eq!(Board::new().hash(), initial_hash);
+        assert_ne!(Board::empty().hash(), initial_hash);
+
+        board.set_squares(&vec![(3, 2).into(), (3, 3).into()], Piece::White);
+        assert_ne!(board.hash(), initial_hash);
+
+        board.set_squares(&vec![(3, 3).into()], Piece::Black);
+        let mut other = Board::new();
+        other.set_squares(&vec![(3, 2).into()], Piece::White);
+        assert_eq!(board.hash(), other.hash());
+    }
+
+    #[test]
+    fn stable_discs_spread_from_the_corners() {
+        let mut board = Board::new();
+        board.set_squares(&vec![(0, 0).into(), (0, 1).into(), (0, 2).into(), (1, 0).into(), (1, 1).into()], Piece::Black);
+        board.set_squares(&vec![(0, 3).into()], Piece::White);
+
+        let expected: Vec<Coord> = vec![(0, 0).into(), (0, 1).into(), (0, 2).into(), (1, 0).into(), (1, 1).into()];
+        assert_eq!(board.stable_discs(Piece::Black), expected);
+        assert!(board.stable_discs(Piece::White).is_empty()); // d1 can still be outflanked from e1
+        assert!(Board::new().stable_discs(Piece::Black).is_empty());
+    }
+
+    #[test]
+    fn every_disc_on_a_full_board_is_stable() {
+        let mut board = Board::empty();
+        for row in 0..Board::BOARD_SIZE {
+            let piece = if row % 2 == 0 { Piece::White } else { Piece::Black };
+            board.set_squares(&(0..Board::BOARD_SIZE).map(|col| (row, col).into()).collect(), piece);
+        }
+
+        assert_eq!(board.stable_discs(Piece::White).len(), 32);
+        assert_eq!(board.stable_discs(Piece::Black).len(), 32);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn board_roundtrips_through_json() {
+        let mut board = Board::new();
+        board.set_squares(&vec![(3, 2).into(), (3, 3).into()], Piece::White);
+
+        let json = serde_json::to_string(&board).unwrap();
+        let restored: Board = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored, board);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserializing_malformed_board_fails() {
+        let short_board = serde_json::to_string(&vec![vec![BoardSquare::Unplayed; 8]; 7]).unwrap();
+        assert!(serde_json::from_str::<Board>(&short_board).is_err());
+
+        let mut rows = vec![vec![BoardSquare::Unplayed; 8]; 8];
+        rows[0][0] = BoardSquare::OutOfBounds;
+        let out_of_bounds_board = serde_json::to_string(&rows).unwrap();
+        assert!(serde_json::from_str::<Board>(&out_of_bounds_board).is_err());
+    }
 }
@@ -0,0 +1,333 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::reversi::piece::Piece;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimeControl {
+    // A fixed amount of time for the whole game
+    SuddenDeath { total: Duration },
+    // A fixed amount of time, plus an increment after every move
+    Fischer { total: Duration, increment: Duration },
+    // Once the main time is over, every move must be made within a period;
+    // each period that is fully used up is lost, and losing all of them loses the game
+    ByoYomi { main: Duration, period: Duration, periods: u32 },
+}
+
+// Durations are written as decimal seconds with up to nanosecond precision,
+// so they can be read back exactly (e.g. "300", "2.5", "0.000001")
+pub fn format_seconds(duration: Duration) -> String {
+    let nanos = duration.subsec_nanos();
+    if nanos == 0 { return duration.as_secs().to_string(); }
+
+    let fraction = format!("{:09}", nanos);
+    format!("{}.{}", duration.as_secs(), fraction.trim_end_matches('0'))
+}
+
+pub fn parse_seconds(text: &str) -> Option<Duration> {
+    let (seconds, fraction) = text.split_once('.').unwrap_or((text, ""));
+    let all_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if seconds.is_empty() || fraction.len() > 9 || !all_digits(seconds) || !all_digits(fraction) { return None; }
+
+    let nanos = format!("{:0<9}", fraction).parse().ok()?;
+    Some(Duration::new(seconds.parse().ok()?, nanos))
+}
+
+// Time controls are written with their durations in seconds:
+// "sudden-death 300", "fischer 300+5" and "byo-yomi 600 30x5"
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TimeControl::SuddenDeath { total } => write!(f, "sudden-death {}", format_seconds(*total)),
+            TimeControl::Fischer { total, increment } => {
+                write!(f, "fischer {}+{}", format_seconds(*total), format_seconds(*increment))
+            }
+            TimeControl::ByoYomi { main, period, periods } => {
+                write!(f, "byo-yomi {} {}x{}", format_seconds(*main), format_seconds(*period), periods)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseTimeControlError;
+
+impl fmt::Display for ParseTimeControlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Time control must be like `sudden-death 300`, `fischer 300+5` or `byo-yomi 600 30x5`")
+    }
+}
+
+impl FromStr for TimeControl {
+    type Err = ParseTimeControlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+
+        match words.as_slice() {
+            ["sudden-death", total] => Ok(TimeControl::SuddenDeath { total: parse_seconds(total).ok_or(ParseTimeControlError)? }),
+            ["fischer", times] => {
+                let (total, increment) = times.split_once('+').ok_or(ParseTimeControlError)?;
+                Ok(TimeControl::Fischer {
+                    total: parse_seconds(total).ok_or(ParseTimeControlError)?,
+                    increment: parse_seconds(increment).ok_or(ParseTimeControlError)?,
+                })
+            }
+            ["byo-yomi", main, periods] => {
+                let (period, count) = periods.split_once('x').ok_or(ParseTimeControlError)?;
+                Ok(TimeControl::ByoYomi {
+                    main: parse_seconds(main).ok_or(ParseTimeControlError)?,
+                    period: parse_seconds(period).ok_or(ParseTimeControlError)?,
+                    periods: count.parse().map_err(|_| ParseTimeControlError)?,
+                })
+            }
+            _ => Err(ParseTimeControlError),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Clock {
+    control: TimeControl,
+    remaining: Duration,
+    periods_left: u32,
+    flagged: bool,
+}
+
+impl Clock {
+    pub fn new(control: TimeControl) -> Clock {
+        let (remaining, periods_left) = match control {
+            TimeControl::SuddenDeath { total } => (total, 0),
+            TimeControl::Fischer { total, .. } => (total, 0),
+            TimeControl::ByoYomi { main, periods, .. } => (main, periods),
+        };
+
+        Clock { control, remaining, periods_left, flagged: false }
+    }
+
+    // Restores a clock that was partially used, like one read from a saved game
+    pub fn with_remaining(control: TimeControl, remaining: Duration, periods_left: u32) -> Clock {
+        Clock { control, remaining, periods_left, flagged: false }
+    }
+
+    pub fn control(&self) -> TimeControl {
+        self.control
+    }
+
+    // Main time left, not counting byo-yomi periods
+    pub fn remaining(&self) -> Duration {
+        self.remaining
+    }
+
+    pub fn periods_left(&self) -> u32 {
+        self.periods_left
+    }
+
+    pub fn is_flagged(&self) -> bool {
+        self.flagged
+    }
+
+    // How long the player can think about the current move before losing on time
+    pub fn time_for_move(&self) -> Duration {
+        match self.control {
+            TimeControl::ByoYomi { period, .. } => self.remaining + period * self.periods_left,
+            _ => self.remaining,
+        }
+    }
+
+    // How long the player can think about the current move without losing any
+    // time for the following moves, such as a byo-yomi period or an increment
+    pub fn free_time_for_move(&self) -> Duration {
+        match self.control {
+            TimeControl::ByoYomi { period, .. } if self.remaining.is_zero() && self.periods_left > 0 => period,
+            TimeControl::Fischer { increment, .. } => increment.min(self.remaining),
+            _ => Duration::ZERO,
+        }
+    }
+
+    // Charges the time spent on a move, returning false if it made the player lose on time
+    pub fn spend(&mut self, elapsed: Duration) -> bool {
+        if self.flagged { return false; }
+
+        match self.control {
+            TimeControl::SuddenDeath { .. } => {
+                self.spend_main_time(elapsed);
+            }
+            TimeControl::Fischer { increment, .. } => {
+                if self.spend_main_time(elapsed) {
+                    self.remaining += increment;
+                }
+            }
+            TimeControl::ByoYomi { period, .. } => {
+                let overtime = elapsed.saturating_sub(self.remaining);
+                self.remaining = self.remaining.saturating_sub(elapsed);
+
+                // A period that is used in full is lost, a partially used one is reset
+                let periods_used = if period.is_zero() { 0 } else { (overtime.as_nanos() / period.as_nanos()) as u32 };
+                if !overtime.is_zero() && (period.is_zero() || periods_used >= self.periods_left) {
+                    self.periods_left = 0;
+                    self.flagged = true;
+                } else {
+                    self.periods_left -= periods_used;
+                }
+            }
+        }
+
+        !self.flagged
+    }
+
+    fn spend_main_time(&mut self, elapsed: Duration) -> bool {
+        if elapsed >= self.remaining {
+            self.remaining = Duration::ZERO;
+            self.flagged = true;
+        } else {
+            self.remaining -= elapsed;
+        }
+
+        !self.flagged
+    }
+}
+
+// Clocks are shown like a regular game clock (e.g. "4:05.3"), followed by the
+// number of byo-yomi periods left when the time control has them
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let tenths = self.remaining.as_millis() / 100;
+        write!(f, "{}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)?;
+
+        if let TimeControl::ByoYomi { .. } = self.control {
+            write!(f, " ({} periods)", self.periods_left)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Clocks {
+    white: Clock,
+    black: Clock,
+}
+
+impl Clocks {
+    pub fn new(control: TimeControl) -> Clocks {
+        Clocks { white: Clock::new(control), black: Clock::new(control) }
+    }
+
+    pub fn from_clocks(white: Clock, black: Clock) -> Clocks {
+        Clocks { white, black }
+    }
+
+    pub fn get(&self, player: Piece) -> &Clock {
+        match player {
+            Piece::White => &self.white,
+            Piece::Black => &self.black,
+        }
+    }
+
+    pub fn get_mut(&mut self, player: Piece) -> &mut Clock {
+        match player {
+            Piece::White => &mut self.white,
+            Piece::Black => &mut self.black,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{format_seconds, parse_seconds, Clock, TimeControl};
+
+    fn secs(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn sudden_death_clock_runs_out() {
+        let mut clock = Clock::new(TimeControl::SuddenDeath { total: secs(60) });
+
+        assert!(clock.spend(secs(40)));
+        assert_eq!(clock.remaining(), secs(20));
+
+        assert!(!clock.spend(secs(20)));
+        assert!(clock.is_flagged());
+        assert_eq!(clock.remaining(), Duration::ZERO);
+    }
+
+    #[test]
+    fn fischer_clock_adds_increment_after_each_move() {
+        let mut clock = Clock::new(TimeControl::Fischer { total: secs(60), increment: secs(5) });
+
+        assert!(clock.spend(secs(10)));
+        assert_eq!(clock.remaining(), secs(55));
+
+        assert!(clock.spend(secs(2)));
+        assert_eq!(clock.remaining(), secs(58));
+
+        // The increment doesn't save a player that is already out of time
+        assert!(!clock.spend(secs(70)));
+        assert_eq!(clock.remaining(), Duration::ZERO);
+    }
+
+    #[test]
+    fn byo_yomi_clock_loses_periods_only_when_fully_used() {
+        let mut clock = Clock::new(TimeControl::ByoYomi { main: secs(60), period: secs(10), periods: 3 });
+
+        assert!(clock.spend(secs(65))); // 5s into the first period, which is reset
+        assert_eq!(clock.remaining(), Duration::ZERO);
+        assert_eq!(clock.periods_left(), 3);
+        assert_eq!(clock.time_for_move(), secs(30));
+
+        assert!(clock.spend(secs(15))); // one full period used
+        assert_eq!(clock.periods_left(), 2);
+
+        assert!(clock.spend(secs(9)));
+        assert_eq!(clock.periods_left(), 2);
+
+        assert!(!clock.spend(secs(20))); // both remaining periods used
+        assert!(clock.is_flagged());
+    }
+
+    #[test]
+    fn clock_displays_remaining_time() {
+        let clock = Clock::with_remaining(TimeControl::SuddenDeath { total: secs(300) }, Duration::from_millis(245_300), 0);
+        assert_eq!(clock.to_string(), "4:05.3");
+
+        let clock = Clock::new(TimeControl::ByoYomi { main: secs(0), period: secs(30), periods: 5 });
+        assert_eq!(clock.to_string(), "0:00.0 (5 periods)");
+    }
+
+    #[test]
+    fn seconds_roundtrip_through_text() {
+        for duration in [secs(300), Duration::from_millis(2500), Duration::from_nanos(1), Duration::new(12, 345_678_901)].iter() {
+            assert_eq!(parse_seconds(&format_seconds(*duration)), Some(*duration));
+        }
+
+        assert_eq!(format_seconds(Duration::from_millis(2500)), "2.5");
+        assert_eq!(parse_seconds("1.25"), Some(Duration::from_millis(1250)));
+        assert_eq!(parse_seconds("-1"), None);
+        assert_eq!(parse_seconds(".5"), None);
+        assert_eq!(parse_seconds("1.0000000001"), None);
+    }
+
+    #[test]
+    fn time_controls_roundtrip_through_text() {
+        let controls = [
+            TimeControl::SuddenDeath { total: secs(300) },
+            TimeControl::Fischer { total: secs(300), increment: Duration::from_millis(2500) },
+            TimeControl::ByoYomi { main: secs(600), period: secs(30), periods: 5 },
+        ];
+
+        for control in controls.iter() {
+            assert_eq!(control.to_string().parse::<TimeControl>(), Ok(*control));
+        }
+
+        assert_eq!("fischer 300+5".parse(), Ok(TimeControl::Fischer { total: secs(300), increment: secs(5) }));
+        assert!("fischer 300".parse::<TimeControl>().is_err());
+        assert!("byo-yomi 600 30".parse::<TimeControl>().is_err());
+        assert!("sudden-death -1".parse::<TimeControl>().is_err());
+        assert!("blitz 60".parse::<TimeControl>().is_err());
+    }
+}
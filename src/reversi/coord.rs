@@ -1,10 +1,15 @@
-#[derive(Debug, Clone, Copy, PartialEq)]
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Coord {
     pub row: usize,
     pub col: usize,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vector {
     pub row: isize,
     pub col: isize,
@@ -36,3 +41,62 @@ impl Coord {
         }
     }
 }
+
+// Coords are written in the usual Reversi notation, column letter followed by
+// row number (e.g. "d3"), matching the headers printed by the board
+impl fmt::Display for Coord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", (b'a' + self.col as u8) as char, self.row + 1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseCoordError;
+
+impl fmt::Display for ParseCoordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Coordinates must be a column letter followed by a row number, like d3")
+    }
+}
+
+impl FromStr for Coord {
+    type Err = ParseCoordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.trim().chars();
+
+        let col = match chars.next() {
+            Some(letter) if letter.is_ascii_alphabetic() => letter.to_ascii_lowercase() as usize - 'a' as usize,
+            _ => return Err(ParseCoordError),
+        };
+
+        let row: usize = chars.as_str().parse().map_err(|_| ParseCoordError)?;
+        if row == 0 { return Err(ParseCoordError); }
+
+        Ok(Coord { row: row - 1, col })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Coord;
+
+    #[test]
+    fn coord_displays_in_reversi_notation() {
+        assert_eq!(Coord::from((0, 0)).to_string(), "a1");
+        assert_eq!(Coord::from((2, 3)).to_string(), "d3");
+        assert_eq!(Coord::from((7, 7)).to_string(), "h8");
+    }
+
+    #[test]
+    fn coord_parses_from_reversi_notation() {
+        assert_eq!("d3".parse(), Ok(Coord::from((2, 3))));
+        assert_eq!("H8".parse(), Ok(Coord::from((7, 7))));
+        assert_eq!(" c4 ".parse(), Ok(Coord::from((3, 2))));
+
+        assert!("".parse::<Coord>().is_err());
+        assert!("d".parse::<Coord>().is_err());
+        assert!("d0".parse::<Coord>().is_err());
+        assert!("3d".parse::<Coord>().is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use std::time::{Duration, Instant};
+
+use crate::reversi::coord::Coord;
+use crate::reversi::game::{Game, GameState, PlayError};
+use crate::reversi::save::{Player, SavedGame};
+
+// Runs a match between the configured players: engine moves are requested
+// from the engines, and every move is charged to the mover's clock, ending the
+// game when a player runs out of time
+pub struct MatchDriver {
+    session: SavedGame,
+    turn_started: Instant,
+}
+
+impl MatchDriver {
+    pub fn new(session: SavedGame) -> MatchDriver {
+        MatchDriver { session, turn_started: Instant::now() }
+    }
+
+    pub fn session(&self) -> &SavedGame {
+        &self.session
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.session.game
+    }
+
+    pub fn current_player(&self) -> &Player {
+        self.session.players.get(self.session.game.current_turn().player)
+    }
+
+    // Time the current player has spent on this move so far
+    pub fn elapsed(&self) -> Duration {
+        self.turn_started.elapsed()
+    }
+
+    pub fn play(&mut self, coord: Coord) -> Result<(), PlayError> {
+        let elapsed = self.elapsed();
+        self.play_timed(coord, elapsed)
+    }
+
+    // Plays a move that took the given time; invalid moves are not charged,
+    // but the time keeps running until a valid one is played
+    pub fn play_timed(&mut self, coord: Coord, elapsed: Duration) -> Result<(), PlayError> {
+        if self.flag_if_out_of_time(elapsed) { return Err(PlayError::OutOfTime); }
+
+        let player = self.session.game.current_turn().player;
+        self.session.game.try_play(coord)?;
+
+        if let Some(clocks) = &mut self.session.clocks {
+            clocks.get_mut(player).spend(elapsed);
+        }
+
+        self.turn_started = Instant::now();
+        Ok(())
+    }
+
+    // Asks the current player's engine for a move and plays it, returning the
+    // move, or None if the current player is human or the game is over
+    pub fn play_engine_move(&mut self) -> Option<Result<Coord, PlayError>> {
+        if self.session.game.state() == GameState::GameOver { return None; }
+
+        let engine = self.current_player().engine?;
+        let player = self.session.game.current_turn().player;
+        let clock = self.session.clocks.as_ref().map(|clocks| clocks.get(player));
+
+        let coord = engine.choose_move(&self.session.game, clock)?;
+        Some(self.play(coord).map(|_| coord))
+    }
+
+    // Ends the game if the current player has run out of time, returning
+    // whether that happened
+    pub fn check_time(&mut self) -> bool {
+        let elapsed = self.elapsed();
+        self.flag_if_out_of_time(elapsed)
+    }
+
+    fn flag_if_out_of_time(&mut self, elapsed: Duration) -> bool {
+        if self.session.game.state() == GameState::GameOver { return false; }
+
+        let player = self.session.game.current_turn().player;
+        let clock = match &mut self.session.clocks {
+            Some(clocks) => clocks.get_mut(player),
+            None => return false,
+        };
+
+        if elapsed < clock.time_for_move() { return false; }
+
+        clock.spend(elapsed);
+        let _ = self.session.game.lose_on_time(player);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::MatchDriver;
+    use crate::reversi::clock::{Clocks, TimeControl};
+    use crate::reversi::engine::Engine;
+    use crate::reversi::game::{Game, GameResult, GameState, PlayError};
+    use crate::reversi::piece::Piece;
+    use crate::reversi::save::{Players, SavedGame};
+
+    fn timed_driver(control: TimeControl) -> MatchDriver {
+        MatchDriver::new(SavedGame::new(Players::default(), Game::new(), Some(Clocks::new(control))))
+    }
+
+    #[test]
+    fn moves_are_charged_to_the_mover() {
+        let mut driver = timed_driver(TimeControl::Fischer { total: Duration::from_secs(60), increment: Duration::from_secs(2) });
+
+        assert!(driver.play_timed("d3".parse().unwrap(), Duration::from_secs(10)).is_ok());
+
+        let clocks = driver.session().clocks.unwrap();
+        assert_eq!(clocks.get(Piece::White).remaining(), Duration::from_secs(52));
+        assert_eq!(clocks.get(Piece::Black).remaining(), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn invalid_moves_are_not_charged() {
+        let mut driver = timed_driver(TimeControl::SuddenDeath { total: Duration::from_secs(60) });
+
+        assert!(matches!(driver.play_timed("a1".parse().unwrap(), Duration::from_secs(10)), Err(PlayError::InvalidMove)));
+
+        assert_eq!(driver.session().clocks.unwrap().get(Piece::White).remaining(), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn running_out_of_time_loses_the_game() {
+        let mut driver = timed_driver(TimeControl::SuddenDeath { total: Duration::from_secs(60) });
+
+        assert!(matches!(driver.play_timed("d3".parse().unwrap(), Duration::from_secs(61)), Err(PlayError::OutOfTime)));
+
+        assert_eq!(driver.game().state(), GameState::GameOver);
+        assert_eq!(driver.game().result(), Some(GameResult::WinOnTime(Piece::Black)));
+        assert!(driver.session().clocks.unwrap().get(Piece::White).is_flagged());
+    }
+
+    #[test]
+    fn engines_play_against_each_other_until_the_end() {
+        let mut players = Players::default();
+        players.white.engine = Some(Engine::new(1));
+        players.black.engine = Some(Engine::new(1));
+        let mut driver = MatchDriver::new(SavedGame::new(players, Game::new(), None));
+
+        while let Some(result) = driver.play_engine_move() {
+            assert!(result.is_ok());
+        }
+
+        assert_eq!(driver.game().state(), GameState::GameOver);
+    }
+
+    #[test]
+    fn human_players_are_not_played_by_the_driver() {
+        let mut driver = timed_driver(TimeControl::SuddenDeath { total: Duration::from_secs(60) });
+
+        assert!(driver.play_engine_move().is_none());
+    }
+}
@@ -0,0 +1,691 @@
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::fmt;
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+use crate::reversi::board::Board;
+use crate::reversi::clock::Clock;
+use crate::reversi::coord::Coord;
+use crate::reversi::game::{Game, GameState};
+use crate::reversi::piece::{BoardSquare, Piece};
+use crate::reversi::rules::WinCondition;
+use crate::reversi::time_manager::TimeManager;
+use crate::reversi::transposition::{Bound, Entry, TranspositionTable};
+
+// Classic positional weights: corners are very good, the squares next to them
+// give corners away, and edges are mildly good
+const SQUARE_WEIGHTS: [[i32; Board::BOARD_SIZE]; Board::BOARD_SIZE] = [
+    [100, -20, 10,  5,  5, 10, -20, 100],
+    [-20, -50, -2, -2, -2, -2, -50, -20],
+    [ 10,  -2, -1, -1, -1, -1,  -2,  10],
+    [  5,  -2, -1, -1, -1, -1,  -2,   5],
+    [  5,  -2, -1, -1, -1, -1,  -2,   5],
+    [ 10,  -2, -1, -1, -1, -1,  -2,  10],
+    [-20, -50, -2, -2, -2, -2, -50, -20],
+    [100, -20, 10,  5,  5, 10, -20, 100],
+];
+
+const MOBILITY_WEIGHT: i32 = 5;
+const STABILITY_WEIGHT: i32 = 15;
+const WIN_SCORE: i32 = 10_000;
+const INFINITY: i32 = WIN_SCORE * 2;
+const ASPIRATION_WINDOW: i32 = 50;
+
+// Lets a search be stopped from another thread; the search then returns the
+// best move of the deepest iteration it completed
+#[derive(Clone, Debug, Default)]
+pub struct StopSignal(Arc<AtomicBool>);
+
+impl StopSignal {
+    pub fn new() -> StopSignal {
+        StopSignal::default()
+    }
+
+    pub fn stop(&self) {
+        self.0.store(true, AtomicOrdering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(AtomicOrdering::Relaxed)
+    }
+}
+
+// A static evaluation to search with in place of the engine's own, like a
+// neural network. Finished games are still scored by the engine, so scores
+// should stay well below a won game's.
+pub trait Evaluator: Send + Sync {
+    // Scores a position that isn't over for the given player
+    fn evaluate(&self, game: &Game, player: Piece) -> i32;
+
+    // How promising the moves of the player to move are, so the best ones are
+    // searched first; moves left out are searched last
+    fn priors(&self, _game: &Game) -> Vec<(Coord, f32)> {
+        Vec::new()
+    }
+}
+
+impl fmt::Debug for dyn Evaluator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Evaluator")
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+    pub max_depth: u32, // the search never goes deeper than the engine's depth either
+    pub time: Option<TimeManager>,
+    pub stop: Option<StopSignal>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchResult {
+    pub best_move: Option<Coord>,
+    pub score: i32,
+    pub depth: u32, // deepest iteration that was completed
+    pub nodes: u64,
+    pub stopped: bool,
+    pub pv: Vec<Coord>, // the expected line of play, starting with the best move
+}
+
+// A move of the player to move, scored by searching the position after it
+#[derive(Clone, Debug, PartialEq)]
+pub struct RankedMove {
+    pub coord: Coord,
+    pub score: i32, // for the player making the move
+    pub depth: u32, // including the move itself
+    pub pv: Vec<Coord>,
+}
+
+// Deadlines, stop signals and the transposition table used by one search thread
+struct SearchContext<'a> {
+    soft_deadline: Option<Instant>,
+    hard_deadline: Option<Instant>,
+    stops: [Option<&'a StopSignal>; 2],
+    table: &'a TranspositionTable,
+    evaluator: Option<&'a dyn Evaluator>,
+    nodes: u64,
+}
+
+impl SearchContext<'_> {
+    fn should_abort(&self) -> bool {
+        self.stops.iter().flatten().any(|stop| stop.is_stopped())
+            || self.hard_deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Engine {
+    depth: u32,
+    threads: usize,
+}
+
+impl Engine {
+    pub const DEFAULT_DEPTH: u32 = 4;
+
+    pub fn new(depth: u32) -> Engine {
+        Engine { depth: depth.max(1), threads: 1 }
+    }
+
+    pub fn with_threads(self, threads: usize) -> Engine {
+        Engine { threads: threads.max(1), ..self }
+    }
+
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    // Picks a move for the current player, keeping within the time given by
+    // their clock when there is one
+    pub fn choose_move(&self, game: &Game, clock: Option<&Clock>) -> Option<Coord> {
+        let empties = game.board().count_squares(BoardSquare::Unplayed);
+        let limits = SearchLimits {
+            max_depth: self.depth,
+            time: clock.map(|clock| TimeManager::for_move(clock, empties)),
+            stop: None,
+        };
+
+        self.search(game, &limits).best_move
+    }
+
+    // Runs the search on the configured number of threads (Lazy SMP): helper
+    // threads search the same position on their own copy of the game, sharing
+    // what they find through the transposition table, which speeds up the
+    // main thread. Only the main thread's result is used, and the helpers are
+    // stopped as soon as it finishes. The search goes as deep as both the
+    // limits and the engine's depth allow.
+    pub fn search(&self, game: &Game, limits: &SearchLimits) -> SearchResult {
+        self.run(game, limits, None)
+    }
+
+    // Searches like `search`, scoring positions and ordering moves with the
+    // given evaluator
+    pub fn search_with(&self, game: &Game, limits: &SearchLimits, evaluator: &dyn Evaluator) -> SearchResult {
+        self.run(game, limits, Some(evaluator))
+    }
+
+    fn run(&self, game: &Game, limits: &SearchLimits, evaluator: Option<&dyn Evaluator>) -> SearchResult {
+        let max_depth = limits.max_depth.min(self.depth);
+        let table = TranspositionTable::default();
+        let helpers_stop = StopSignal::new();
+
+        // The clock is only read when there's a time limit, since some targets,
+        // like WebAssembly in browsers, have no clock to read
+        let deadlines = limits.time.map(|time| {
+            let start = Instant::now();
+            (time.soft_deadline(start), time.hard_deadline(start))
+        });
+
+        // Helpers keep going until the main thread is done, so they only stop
+        // at the hard deadline or when told to
+        let context = |is_helper: bool| SearchContext {
+            soft_deadline: deadlines.filter(|_| !is_helper).map(|(soft, _)| soft),
+            hard_deadline: deadlines.map(|(_, hard)| hard),
+            stops: [limits.stop.as_ref(), Some(&helpers_stop).filter(|_| is_helper)],
+            table: &table,
+            evaluator,
+            nodes: 0,
+        };
+
+        thread::scope(|scope| {
+            let helpers: Vec<_> = (1..self.threads)
+                .map(|id| {
+                    let mut helper_context = context(true);
+                    let helper_game = game.clone();
+
+                    // Half of the helpers start one ply deeper, so threads don't all search the same depth
+                    scope.spawn(move || self.iterate(&helper_game, max_depth, 1 + id as u32 % 2, &mut helper_context))
+                })
+                .collect();
+
+            let mut result = self.iterate(game, max_depth, 1, &mut context(false));
+            helpers_stop.stop();
+
+            for helper in helpers {
+                result.nodes += helper.join().map_or(0, |helper_result| helper_result.nodes);
+            }
+            result.pv = Engine::principal_variation(game, result.best_move, result.depth, &table);
+            result
+        })
+    }
+
+    // Follows the best moves kept in the transposition table from the root,
+    // checking that each one is valid in case of a hash collision
+    fn principal_variation(game: &Game, best_move: Option<Coord>, depth: u32, table: &TranspositionTable) -> Vec<Coord> {
+        let mut pv = Vec::new();
+        let mut position = game.clone();
+        let mut next = best_move;
+
+        while let Some(coord) = next {
+            if pv.len() >= depth.max(1) as usize || position.try_play(coord).is_err() { break; }
+            pv.push(coord);
+            next = table.probe(position.position_hash()).and_then(|entry| entry.best_move);
+        }
+
+        pv
+    }
+
+    // Scores every move of the player to move by searching the position after
+    // it one ply shallower, best first. A time limit is shared equally between
+    // the moves, and the stop signal stops all of them.
+    pub fn rank_moves(&self, game: &Game, limits: &SearchLimits) -> Vec<RankedMove> {
+        let player = game.current_turn().player;
+        let moves = &game.current_turn().valid_moves;
+        if game.state() == GameState::GameOver { return Vec::new(); }
+
+        let share = moves.len() as u32;
+        let child_limits = SearchLimits {
+            max_depth: limits.max_depth.min(self.depth).saturating_sub(1),
+            time: limits.time.map(|time| TimeManager::new(time.soft_limit() / share, time.hard_limit() / share)),
+            stop: limits.stop.clone(),
+        };
+
+        let mut ranked: Vec<RankedMove> = moves.iter()
+            .map(|mv| {
+                let coord = *mv.coord();
+                let mut child = game.clone();
+                let _ = child.try_play(coord);
+
+                if child.state() == GameState::GameOver || child_limits.max_depth == 0 {
+                    return RankedMove { coord, score: Engine::evaluate(&child, player), depth: 1, pv: vec![coord] };
+                }
+
+                let result = self.search(&child, &child_limits);
+                let score = if child.current_turn().player == player { result.score } else { -result.score };
+                RankedMove { coord, score, depth: result.depth + 1, pv: std::iter::once(coord).chain(result.pv).collect() }
+            })
+            .collect();
+
+        ranked.sort_by_key(|ranked_move| -ranked_move.score);
+        ranked
+    }
+
+    // Searches to the end of the game on one thread, returning the best move
+    // and the final disc difference for the player to move with perfect play
+    // from both sides, counted the other way when fewer discs win. This is
+    // only fast enough with few empty squares left.
+    pub fn solve(&self, game: &Game) -> (Option<Coord>, i32) {
+        let player = game.current_turn().player;
+        if game.state() == GameState::GameOver {
+            return (None, Engine::disc_difference(Engine::evaluate(game, player)));
+        }
+
+        let table = TranspositionTable::default();
+        let mut context = SearchContext { soft_deadline: None, hard_deadline: None, stops: [None, None], table: &table, evaluator: None, nodes: 0 };
+
+        // Every move fills a square, so the game is over within this many moves
+        let empties = game.board().count_squares(BoardSquare::Unplayed) as u32;
+        let (score, best_move) = self.search_root(game, empties, -INFINITY, INFINITY, None, &mut context).expect("solving can't be stopped");
+        (best_move, Engine::disc_difference(score))
+    }
+
+    // Whether a score is that of a finished game, which the search saw through
+    pub fn is_decided(score: i32) -> bool {
+        score.abs() >= WIN_SCORE
+    }
+
+    // The disc difference of a finished game's score
+    pub fn disc_difference(score: i32) -> i32 {
+        match score {
+            score if score >= WIN_SCORE => score - WIN_SCORE,
+            score if score <= -WIN_SCORE => score + WIN_SCORE,
+            _ => 0,
+        }
+    }
+
+    // Searches one ply deeper at a time, up to the maximum depth, until the
+    // time runs out or the search is stopped. Each iteration starts with a
+    // narrow window around the previous score, which is widened if the score
+    // falls outside of it.
+    fn iterate(&self, game: &Game, max_depth: u32, first_depth: u32, context: &mut SearchContext) -> SearchResult {
+        let mut result = SearchResult {
+            best_move: game.current_turn().valid_moves.first().map(|mv| *mv.coord()),
+            score: 0,
+            depth: 0,
+            nodes: 0,
+            stopped: false,
+            pv: Vec::new(),
+        };
+
+        for depth in first_depth..=max_depth.max(1) {
+            if context.soft_deadline.is_some_and(|deadline| Instant::now() >= deadline) { break; }
+
+            let (mut alpha, mut beta) = match result.depth {
+                0 => (-INFINITY, INFINITY),
+                _ => (result.score - ASPIRATION_WINDOW, result.score + ASPIRATION_WINDOW),
+            };
+
+            let iteration = loop {
+                match self.search_root(game, depth, alpha, beta, result.best_move, context) {
+                    Some((score, _)) if score <= alpha && alpha > -INFINITY => alpha = -INFINITY,
+                    Some((score, _)) if score >= beta && beta < INFINITY => beta = INFINITY,
+                    other => break other,
+                }
+            };
+
+            match iteration {
+                Some((score, best_move)) => {
+                    result.score = score;
+                    result.best_move = best_move.or(result.best_move);
+                    result.depth = depth;
+                }
+                None => {
+                    result.stopped = true;
+                    break;
+                }
+            }
+
+            // No need to look deeper once the game is decided
+            if Engine::is_decided(result.score) { break; }
+        }
+
+        result.nodes = context.nodes;
+        result
+    }
+
+    // Searches every move of the current player, starting with the best move
+    // of the previous iteration, or returns None if the search was aborted
+    fn search_root(&self, game: &Game, depth: u32, mut alpha: i32, beta: i32, first: Option<Coord>, context: &mut SearchContext) -> Option<(i32, Option<Coord>)> {
+        let player = game.current_turn().player;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+
+        for coord in Engine::ordered_moves(game, first, context.evaluator) {
+            let mut child = game.clone();
+            let _ = child.try_play(coord);
+
+            let score = if child.current_turn().player == player {
+                self.negamax(&child, depth - 1, alpha, beta, context)?
+            } else {
+                -self.negamax(&child, depth - 1, -beta, -alpha, context)?
+            };
+
+            if score > best_score {
+                best_score = score;
+                best_move = Some(coord);
+            }
+
+            alpha = alpha.max(score);
+            if alpha >= beta { break; }
+        }
+
+        Some((best_score, best_move))
+    }
+
+    // Scores the game from the point of view of the player to move, or returns
+    // None if the search was aborted. Results are kept in the transposition
+    // table, so positions reached again (or by other threads) are not searched twice.
+    fn negamax(&self, game: &Game, depth: u32, mut alpha: i32, beta: i32, context: &mut SearchContext) -> Option<i32> {
+        context.nodes += 1;
+        if context.should_abort() { return None; }
+
+        let player = game.current_turn().player;
+        if game.state() == GameState::GameOver {
+            return Some(Engine::evaluate(game, player));
+        }
+        if depth == 0 {
+            return Some(context.evaluator.map_or_else(|| Engine::evaluate(game, player), |evaluator| evaluator.evaluate(game, player)));
+        }
+
+        let hash = game.position_hash();
+        let entry = context.table.probe(hash);
+        if let Some(entry) = entry.filter(|entry| entry.depth >= depth) {
+            match entry.bound {
+                Bound::Exact => return Some(entry.score),
+                Bound::Lower if entry.score >= beta => return Some(entry.score),
+                Bound::Upper if entry.score <= alpha => return Some(entry.score),
+                _ => (),
+            }
+        }
+
+        let original_alpha = alpha;
+        let mut best = -INFINITY;
+        let mut best_move = None;
+        for coord in Engine::ordered_moves(game, entry.and_then(|entry| entry.best_move), context.evaluator) {
+            let mut child = game.clone();
+            let _ = child.try_play(coord);
+
+            // After a pass, the same player moves again so the score keeps its sign
+            let score = if child.current_turn().player == player {
+                self.negamax(&child, depth - 1, alpha, beta, context)?
+            } else {
+                -self.negamax(&child, depth - 1, -beta, -alpha, context)?
+            };
+
+            if score > best {
+                best = score;
+                best_move = Some(coord);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta { break; }
+        }
+
+        let bound = if best <= original_alpha {
+            Bound::Upper
+        } else if best >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        context.table.store(hash, Entry { depth, score: best, bound, best_move });
+
+        Some(best)
+    }
+
+    // Valid moves of the current player, with the given move first and the
+    // others by the evaluator's priors, if there's one
+    fn ordered_moves(game: &Game, first: Option<Coord>, evaluator: Option<&dyn Evaluator>) -> Vec<Coord> {
+        let mut moves: Vec<Coord> = game.current_turn().valid_moves.iter().map(|mv| *mv.coord()).collect();
+        if let Some(evaluator) = evaluator {
+            let priors = evaluator.priors(game);
+            let prior = |coord: &Coord| priors.iter().find(|(prior_coord, _)| prior_coord == coord).map_or(f32::NEG_INFINITY, |(_, prior)| *prior);
+            moves.sort_by(|a, b| prior(b).total_cmp(&prior(a)));
+        }
+        if let Some(index) = moves.iter().position(|coord| Some(*coord) == first) {
+            moves[..=index].rotate_right(1);
+        }
+        moves
+    }
+
+    // Finished games are scored by disc difference, well above any positional
+    // score; otherwise squares are weighted, and mobility and stable discs
+    // are rewarded. When the player with fewer discs wins, owning squares and
+    // stable discs counts against the player instead, while mobility, which
+    // lets them avoid taking discs, is still good.
+    pub fn evaluate(game: &Game, player: Piece) -> i32 {
+        let board = game.board();
+
+        if game.state() == GameState::GameOver {
+            return match game.rules().margin(board, player) {
+                0 => 0,
+                margin if margin > 0 => WIN_SCORE + margin,
+                margin => -WIN_SCORE + margin,
+            };
+        }
+
+        let mut score = 0;
+        for (row, weights) in SQUARE_WEIGHTS.iter().enumerate() {
+            for (col, weight) in weights.iter().enumerate() {
+                match board.get_coord_square_at((row, col).into()) {
+                    (_, BoardSquare::Played(piece)) if piece == player => score += weight,
+                    (_, BoardSquare::Played(_)) => score -= weight,
+                    _ => (),
+                }
+            }
+        }
+
+        // Stable discs nearly always grow from a corner, so they're only worth
+        // counting once one is taken
+        let corners = [(0, 0), (0, Board::BOARD_SIZE - 1), (Board::BOARD_SIZE - 1, 0), (Board::BOARD_SIZE - 1, Board::BOARD_SIZE - 1)];
+        if corners.iter().any(|corner| board.get_coord_square_at((*corner).into()).1 != BoardSquare::Unplayed) {
+            let stability = board.stable_discs(player).len() as i32 - board.stable_discs(player.opponent()).len() as i32;
+            score += stability * STABILITY_WEIGHT;
+        }
+
+        if game.rules().win_condition == WinCondition::FewestDiscs {
+            score = -score;
+        }
+
+        let mobility = game.current_turn().valid_moves.len() as i32;
+        if game.current_turn().player == player {
+            score + mobility * MOBILITY_WEIGHT
+        } else {
+            score - mobility * MOBILITY_WEIGHT
+        }
+    }
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Engine::new(Engine::DEFAULT_DEPTH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use super::{Engine, SearchLimits, StopSignal};
+    use crate::reversi::board::Board;
+    use crate::reversi::coord::Coord;
+    use crate::reversi::game::Game;
+    use crate::reversi::piece::Piece;
+    use crate::reversi::rules::Rules;
+    use crate::reversi::time_manager::TimeManager;
+    use crate::reversi::transposition::TranspositionTable;
+
+    #[test]
+    fn engine_picks_a_valid_move() {
+        let game = Game::new();
+
+        let best_move = Engine::new(3).choose_move(&game, None).unwrap();
+
+        assert!(game.current_turn().valid_moves.iter().any(|mv| *mv.coord() == best_move));
+    }
+
+    #[test]
+    fn solving_finds_the_final_disc_difference() {
+        // White takes h8 and flips f8 and g8, ending the game with all 8 discs
+        let mut board = Board::empty();
+        board.set_squares(&vec![(7, 0).into(), (7, 1).into(), (7, 2).into(), (7, 3).into(), (7, 4).into()], Piece::White);
+        board.set_squares(&vec![(7, 5).into(), (7, 6).into()], Piece::Black);
+        let game = Game::from_position(board, Piece::White);
+
+        assert_eq!(Engine::new(1).solve(&game), (Some(Coord::from((7, 7))), 8));
+    }
+
+    #[test]
+    fn engine_takes_the_corner() {
+        // White can take the corner at (7,7) by capturing (7,6), or play elsewhere
+        let mut board = Board::new();
+        board.set_squares(&vec![(7, 5).into()], Piece::White);
+        board.set_squares(&vec![(7, 6).into()], Piece::Black);
+        let game = Game::from_position(board, Piece::White);
+
+        assert_eq!(Engine::new(1).choose_move(&game, None), Some(Coord::from((7, 7))));
+    }
+
+    #[test]
+    fn engine_gives_the_corner_away_under_misere_rules() {
+        let mut board = Board::new();
+        board.set_squares(&vec![(7, 5).into()], Piece::White);
+        board.set_squares(&vec![(7, 6).into()], Piece::Black);
+        let game = Game::from_position_with_rules(board, Piece::White, Rules::misere());
+
+        assert_ne!(Engine::new(1).choose_move(&game, None), Some(Coord::from((7, 7))));
+    }
+
+    #[test]
+    fn iterative_deepening_reaches_the_maximum_depth() {
+        let game = Game::new();
+
+        let result = Engine::new(4).search(&game, &SearchLimits { max_depth: 4, ..SearchLimits::default() });
+
+        assert_eq!(result.depth, 4);
+        assert!(!result.stopped);
+    }
+
+    #[test]
+    fn principal_variation_is_a_valid_line() {
+        let game = Game::new();
+
+        let result = Engine::new(4).search(&game, &SearchLimits { max_depth: 4, ..SearchLimits::default() });
+
+        assert_eq!(result.pv.first().copied(), result.best_move);
+        assert!(result.pv.len() <= 4);
+        assert!(Game::replay(Board::new(), Piece::White, &result.pv).is_ok());
+    }
+
+    #[test]
+    fn ranking_scores_every_move_best_first() {
+        let mut board = Board::new();
+        board.set_squares(&vec![(7, 5).into()], Piece::White);
+        board.set_squares(&vec![(7, 6).into()], Piece::Black);
+        let game = Game::from_position(board, Piece::White);
+
+        let ranked = Engine::new(3).rank_moves(&game, &SearchLimits { max_depth: 3, ..SearchLimits::default() });
+
+        assert_eq!(ranked.len(), game.current_turn().valid_moves.len());
+        assert!(ranked.windows(2).all(|pair| pair[0].score >= pair[1].score));
+        assert!(ranked.iter().all(|ranked_move| ranked_move.pv[0] == ranked_move.coord && ranked_move.depth == 3));
+        assert_eq!(ranked[0].coord, Coord::from((7, 7)));
+    }
+
+    #[test]
+    fn aspiration_windows_give_the_same_score_as_a_full_search() {
+        let mut game = Game::new();
+        for coord in ["d3", "c5", "f6", "f5"] {
+            assert!(game.try_play(coord.parse().unwrap()).is_ok());
+        }
+
+        // A single iteration at depth 1 has no window, so compare each depth
+        // against the plain search of the same depth
+        for depth in 2..=4 {
+            let limits = SearchLimits { max_depth: depth, ..SearchLimits::default() };
+            let deepened = Engine::new(depth).search(&game, &limits);
+
+            let table = TranspositionTable::default();
+            let mut context = super::SearchContext { soft_deadline: None, hard_deadline: None, stops: [None, None], table: &table, evaluator: None, nodes: 0 };
+            let (score, _) = Engine::new(depth)
+                .search_root(&game, depth, -super::INFINITY, super::INFINITY, None, &mut context)
+                .unwrap();
+
+            assert_eq!(deepened.score, score);
+        }
+    }
+
+    #[test]
+    fn searches_go_no_deeper_than_the_engine() {
+        let game = Game::new();
+        let limits = SearchLimits { max_depth: 6, ..SearchLimits::default() };
+
+        assert_eq!(Engine::new(2).search(&game, &limits).depth, 2);
+        assert!(Engine::new(2).rank_moves(&game, &limits).iter().all(|ranked_move| ranked_move.depth == 2));
+        assert_eq!(Engine::new(6).search(&game, &SearchLimits { max_depth: 3, ..SearchLimits::default() }).depth, 3);
+    }
+
+    #[test]
+    fn search_returns_a_move_when_out_of_time() {
+        let game = Game::new();
+        let limits = SearchLimits {
+            max_depth: 20,
+            time: Some(TimeManager::new(Duration::ZERO, Duration::ZERO)),
+            stop: None,
+        };
+
+        let result = Engine::new(20).search(&game, &limits);
+
+        assert_eq!(result.depth, 0);
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn search_can_be_stopped_on_demand() {
+        let game = Game::new();
+        let stop = StopSignal::new();
+        let limits = SearchLimits { max_depth: 60, time: None, stop: Some(stop.clone()) };
+
+        let search = thread::spawn(move || Engine::new(60).search(&game, &limits));
+        thread::sleep(Duration::from_millis(100));
+        stop.stop();
+        let result = search.join().unwrap();
+
+        assert!(result.stopped);
+        assert!(result.depth >= 1);
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn parallel_search_finds_a_valid_move() {
+        let mut game = Game::new();
+        for coord in ["d3", "c5", "f6", "f5"] {
+            assert!(game.try_play(coord.parse().unwrap()).is_ok());
+        }
+        let limits = SearchLimits { max_depth: 5, ..SearchLimits::default() };
+
+        let result = Engine::new(5).with_threads(4).search(&game, &limits);
+
+        assert_eq!(result.depth, 5);
+        assert!(game.current_turn().valid_moves.iter().any(|mv| Some(*mv.coord()) == result.best_move));
+    }
+
+    #[test]
+    fn parallel_search_can_be_stopped_on_demand() {
+        let game = Game::new();
+        let stop = StopSignal::new();
+        let limits = SearchLimits { max_depth: 60, time: None, stop: Some(stop.clone()) };
+
+        let search = thread::spawn(move || Engine::new(60).with_threads(3).search(&game, &limits));
+        thread::sleep(Duration::from_millis(100));
+        stop.stop();
+        let result = search.join().unwrap();
+
+        assert!(result.stopped);
+        assert!(result.best_move.is_some());
+    }
+}
@@ -0,0 +1,317 @@
+use std::thread;
+
+use crate::reversi::board::Board;
+use crate::reversi::coord::Coord;
+use crate::reversi::engine::Engine;
+use crate::reversi::game::{Game, GameResult, GameState, PlayError};
+use crate::reversi::piece::Piece;
+use crate::reversi::planes::{self, Planes};
+use crate::reversi::random::Rng;
+
+// One action per square, numbered row by row from a1 (0) to h8 (63), and a
+// last one for passing, which keeps the usual action space but is never legal
+pub const ACTION_COUNT: usize = Board::BOARD_SIZE * Board::BOARD_SIZE + 1;
+pub const PASS_ACTION: usize = ACTION_COUNT - 1;
+
+pub fn action_to_coord(action: usize) -> Option<Coord> {
+    match action < PASS_ACTION {
+        true => Some(Board::coord_at(action)),
+        false => None,
+    }
+}
+
+pub fn coord_to_action(coord: Coord) -> usize {
+    coord.row * Board::BOARD_SIZE + coord.col
+}
+
+// Who plays against the agent
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Opponent {
+    Random, // a uniformly random legal move, drawn from the environment's seed
+    Engine(Engine),
+    SelfPlay, // the agent plays both sides, always seeing the board as the player to move
+}
+
+// What the agent sees after a reset or a step. The observation is from the
+// point of view of the player to move (the agent, unless it's self-play),
+// and the reward is 1 for a win, -1 for a loss and 0 otherwise, for the player
+// who took the action.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step {
+    pub observation: Planes,
+    pub reward: f32,
+    pub done: bool,
+    pub legal_actions: [bool; ACTION_COUNT],
+}
+
+// A gym-style environment around a game. Passes are forced by the rules, so
+// the environment makes them on the agent's behalf and the pass action is
+// never legal. Once the game is over, no action is.
+#[derive(Clone)]
+pub struct Environment {
+    game: Game,
+    agent: Piece,
+    opponent: Opponent,
+    rng: Rng,
+}
+
+impl Environment {
+    pub fn new(agent: Piece, opponent: Opponent) -> Environment {
+        Environment { game: Game::new(), agent, opponent, rng: Rng::new(0) }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    // Starts a new game, letting the opponent open when the agent plays black
+    pub fn reset(&mut self, seed: u64) -> Step {
+        self.game = Game::new();
+        self.rng = Rng::new(seed);
+        self.play_opponent_moves();
+        self.observe(0.0)
+    }
+
+    pub fn step(&mut self, action: usize) -> Result<Step, PlayError> {
+        let coord = self.check_action(action)?;
+        let mover = self.game.current_turn().player;
+
+        self.game.try_play(coord)?;
+        self.play_opponent_moves();
+
+        let reward = match self.game.result() {
+            Some(GameResult::Win(winner)) | Some(GameResult::WinOnTime(winner)) if winner == mover => 1.0,
+            Some(GameResult::Win(_)) | Some(GameResult::WinOnTime(_)) => -1.0,
+            Some(GameResult::Draw) | None => 0.0,
+        };
+        Ok(self.observe(reward))
+    }
+
+    pub fn legal_actions(&self) -> [bool; ACTION_COUNT] {
+        let mut legal_actions = [false; ACTION_COUNT];
+        if self.game.state() == GameState::GameOver { return legal_actions; }
+
+        for mv in self.game.current_turn().valid_moves.iter() {
+            legal_actions[coord_to_action(*mv.coord())] = true;
+        }
+
+        legal_actions
+    }
+
+    // The square an action plays on, if the player to move can play it
+    fn check_action(&self, action: usize) -> Result<Coord, PlayError> {
+        if self.game.state() == GameState::GameOver { return Err(PlayError::GameOver); }
+        if action >= ACTION_COUNT { return Err(PlayError::OutOfBounds); }
+
+        match action_to_coord(action) {
+            Some(coord) if self.legal_actions()[action] => Ok(coord),
+            _ => Err(PlayError::InvalidMove),
+        }
+    }
+
+    fn play_opponent_moves(&mut self) {
+        while self.game.state() != GameState::GameOver && self.game.current_turn().player != self.agent {
+            let valid_moves = &self.game.current_turn().valid_moves;
+            let coord = match self.opponent {
+                Opponent::Random => *valid_moves[self.rng.below(valid_moves.len())].coord(),
+                Opponent::Engine(engine) => engine.choose_move(&self.game, None).expect("a game that isn't over has a move"),
+                Opponent::SelfPlay => return,
+            };
+
+            self.game.try_play(coord).expect("opponents only choose valid moves");
+        }
+    }
+
+    fn observe(&self, reward: f32) -> Step {
+        let player = match self.opponent {
+            Opponent::SelfPlay => self.game.current_turn().player,
+            _ => self.agent,
+        };
+
+        Step {
+            observation: planes::encode(self.game.board(), player),
+            reward,
+            done: self.game.state() == GameState::GameOver,
+            legal_actions: self.legal_actions(),
+        }
+    }
+}
+
+// A batch of environments stepped together, split across threads. Finished
+// games are reset straight away, so the step that ends a game carries its
+// reward and `done`, with the observation and legal actions of the next game.
+pub struct VecEnvironment {
+    environments: Vec<Environment>,
+    seeds: Rng,
+    threads: usize,
+}
+
+impl VecEnvironment {
+    pub fn new(count: usize, agent: Piece, opponent: Opponent) -> VecEnvironment {
+        VecEnvironment { environments: vec![Environment::new(agent, opponent); count], seeds: Rng::new(0), threads: 1 }
+    }
+
+    pub fn with_threads(self, threads: usize) -> VecEnvironment {
+        VecEnvironment { threads: threads.max(1), ..self }
+    }
+
+    pub fn len(&self) -> usize {
+        self.environments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.environments.is_empty()
+    }
+
+    pub fn environments(&self) -> &[Environment] {
+        &self.environments
+    }
+
+    // Resets every environment with its own seed, all drawn from this one
+    pub fn reset(&mut self, seed: u64) -> Vec<Step> {
+        self.seeds = Rng::new(seed);
+        let seeds = &mut self.seeds;
+        self.environments.iter_mut().map(|environment| environment.reset(seeds.next_u64())).collect()
+    }
+
+    // Takes one action in each environment. Nothing is played unless every
+    // action is legal; otherwise the index of the first illegal one is returned.
+    pub fn step(&mut self, actions: &[usize]) -> Result<Vec<Step>, (usize, PlayError)> {
+        assert_eq!(actions.len(), self.environments.len(), "one action is needed per environment");
+
+        for (index, (environment, action)) in self.environments.iter().zip(actions).enumerate() {
+            environment.check_action(*action).map_err(|err| (index, err))?;
+        }
+
+        // Seeds for the games that finish are drawn up front, so results don't
+        // depend on the number of threads
+        let seeds: Vec<u64> = actions.iter().map(|_| self.seeds.next_u64()).collect();
+        let chunk_size = self.environments.len().div_ceil(self.threads).max(1);
+
+        let steps = thread::scope(|scope| {
+            let workers: Vec<_> = self.environments
+                .chunks_mut(chunk_size)
+                .zip(actions.chunks(chunk_size).zip(seeds.chunks(chunk_size)))
+                .map(|(environments, (actions, seeds))| {
+                    scope.spawn(move || {
+                        environments.iter_mut().zip(actions).zip(seeds)
+                            .map(|((environment, action), seed)| {
+                                let step = environment.step(*action).expect("actions were checked");
+                                match step.done {
+                                    true => Step { reward: step.reward, done: true, ..environment.reset(*seed) },
+                                    false => step,
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            workers.into_iter().flat_map(|worker| worker.join().expect("environment threads don't panic")).collect()
+        });
+
+        Ok(steps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{action_to_coord, coord_to_action, Environment, Opponent, VecEnvironment, ACTION_COUNT, PASS_ACTION};
+    use crate::reversi::engine::Engine;
+    use crate::reversi::game::PlayError;
+    use crate::reversi::piece::Piece;
+
+    fn first_legal(legal_actions: &[bool; ACTION_COUNT]) -> usize {
+        legal_actions.iter().position(|legal| *legal).unwrap()
+    }
+
+    #[test]
+    fn actions_map_to_squares_and_pass() {
+        assert_eq!(coord_to_action("d3".parse().unwrap()), 19);
+        assert_eq!(action_to_coord(19), Some("d3".parse().unwrap()));
+        assert_eq!(action_to_coord(63), Some("h8".parse().unwrap()));
+        assert_eq!(action_to_coord(PASS_ACTION), None);
+    }
+
+    #[test]
+    fn games_against_a_random_opponent_are_reproducible() {
+        let play = |seed| {
+            let mut environment = Environment::new(Piece::Black, Opponent::Random);
+            let mut step = environment.reset(seed);
+            assert_eq!(environment.game().history().len(), 1); // white opened
+
+            while !step.done {
+                assert!(!step.legal_actions[PASS_ACTION]);
+                step = environment.step(first_legal(&step.legal_actions)).unwrap();
+            }
+
+            assert!(step.legal_actions.iter().all(|legal| !legal));
+            assert!(matches!(environment.step(PASS_ACTION), Err(PlayError::GameOver)));
+            (environment.game().history().to_vec(), step.reward)
+        };
+
+        let (history, reward) = play(7);
+        assert_eq!(play(7), (history, reward));
+        assert!([-1.0, 0.0, 1.0].contains(&reward));
+    }
+
+    #[test]
+    fn illegal_actions_are_rejected() {
+        let mut environment = Environment::new(Piece::White, Opponent::Engine(Engine::new(1)));
+        let step = environment.reset(0);
+
+        assert_eq!(step.legal_actions.iter().filter(|legal| **legal).count(), 4);
+        assert!(matches!(environment.step(0), Err(PlayError::InvalidMove)));
+        assert!(matches!(environment.step(PASS_ACTION), Err(PlayError::InvalidMove)));
+        assert!(matches!(environment.step(ACTION_COUNT), Err(PlayError::OutOfBounds)));
+
+        environment.step(19).unwrap();
+        assert_eq!(environment.game().history().len(), 2); // the engine answered
+    }
+
+    #[test]
+    fn self_play_observes_from_the_player_to_move() {
+        let mut environment = Environment::new(Piece::White, Opponent::SelfPlay);
+        let first = environment.reset(0);
+
+        let second = environment.step(19).unwrap(); // d3
+
+        assert_eq!(environment.game().current_turn().player, Piece::Black);
+        assert_eq!(first.observation[0][3][4], 1.0); // e4 is white's
+        assert_eq!(second.observation[1][3][4], 1.0); // and black's opponent's
+    }
+
+    #[test]
+    fn batches_reset_finished_games() {
+        let play = |threads| {
+            let mut batch = VecEnvironment::new(5, Piece::White, Opponent::Random).with_threads(threads);
+            let mut steps = batch.reset(3);
+            let mut rewards = Vec::new();
+
+            while rewards.len() < 2 * batch.len() {
+                let actions: Vec<usize> = steps.iter().map(|step| first_legal(&step.legal_actions)).collect();
+                steps = batch.step(&actions).unwrap();
+
+                for step in steps.iter().filter(|step| step.done) {
+                    assert!(!step.legal_actions[PASS_ACTION]); // already the next game
+                    rewards.push(step.reward);
+                }
+            }
+            rewards
+        };
+
+        assert_eq!(play(1), play(3));
+    }
+
+    #[test]
+    fn batches_check_every_action_before_playing() {
+        let mut batch = VecEnvironment::new(4, Piece::White, Opponent::Random);
+        batch.reset(0);
+
+        let mut actions = vec![19; batch.len()];
+        actions[2] = 0;
+
+        assert!(matches!(batch.step(&actions), Err((2, PlayError::InvalidMove))));
+        assert!(batch.environments().iter().all(|environment| environment.game().history().is_empty()));
+    }
+}
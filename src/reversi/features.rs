@@ -0,0 +1,260 @@
+use std::fmt;
+
+use crate::reversi::board::Board;
+use crate::reversi::coord::{Coord, Vector};
+use crate::reversi::game::Game;
+use crate::reversi::piece::{BoardSquare, Piece};
+
+const LAST: usize = Board::BOARD_SIZE - 1;
+
+const CORNERS: [(usize, usize); 4] = [(0, 0), (0, LAST), (LAST, 0), (LAST, LAST)];
+
+// The squares diagonally next to the corners, which usually give them away
+const X_SQUARES: [(usize, usize); 4] = [(1, 1), (1, LAST - 1), (LAST - 1, 1), (LAST - 1, LAST - 1)];
+
+// The edge squares next to the corners
+const C_SQUARES: [(usize, usize); 8] = [
+    (0, 1), (1, 0),
+    (0, LAST - 1), (1, LAST),
+    (LAST - 1, 0), (LAST, 1),
+    (LAST, LAST - 1), (LAST - 1, LAST),
+];
+
+const NEIGHBOURS: [(isize, isize); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+
+// Features of one player's position
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PlayerFeatures {
+    pub discs: usize,
+    pub mobility: usize,           // legal moves, whether or not it's the player's turn
+    pub potential_mobility: usize, // empty squares next to an opponent's disc
+    pub frontier: usize,           // discs next to an empty square
+    pub corners: usize,
+    pub x_squares: usize,
+    pub c_squares: usize,
+    pub stable: usize,
+}
+
+// One of the four edges, read from its a1 end (or a8 for the bottom edge)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Edge {
+    pub squares: [BoardSquare; Board::BOARD_SIZE],
+}
+
+impl Edge {
+    // A number for the configuration, from 0 to 3^8 - 1, counting each square
+    // as a base-3 digit (empty 0, black 1, white 2) starting from the corner;
+    // handy for indexing pattern weights
+    pub fn index(&self) -> usize {
+        self.squares.iter().fold(0, |index, square| {
+            index * 3 + match square {
+                BoardSquare::Played(Piece::Black) => 1,
+                BoardSquare::Played(Piece::White) => 2,
+                _ => 0,
+            }
+        })
+    }
+}
+
+impl fmt::Display for Edge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for square in self.squares.iter() {
+            match square {
+                BoardSquare::Played(piece) => write!(f, "{}", piece)?,
+                _ => write!(f, ".")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Features of a position for evaluation tuning and human analysis
+#[derive(Clone, Debug, PartialEq)]
+pub struct Features {
+    pub white: PlayerFeatures,
+    pub black: PlayerFeatures,
+    // Groups of empty squares connected through their sides or corners; an
+    // odd region lets whoever moves there first also move there last
+    pub regions: Vec<Vec<Coord>>,
+    // The top (row 1), bottom (row 8), left (column a) and right (column h) edges
+    pub edges: [Edge; 4],
+}
+
+impl Features {
+    pub fn of(board: &Board) -> Features {
+        let game = Game::from_position(*board, Piece::White);
+
+        Features {
+            white: player_features(board, &game, Piece::White),
+            black: player_features(board, &game, Piece::Black),
+            regions: empty_regions(board),
+            edges: [
+                edge(board, |index| (0, index)),
+                edge(board, |index| (LAST, index)),
+                edge(board, |index| (index, 0)),
+                edge(board, |index| (index, LAST)),
+            ],
+        }
+    }
+
+    pub fn get(&self, piece: Piece) -> &PlayerFeatures {
+        match piece {
+            Piece::White => &self.white,
+            Piece::Black => &self.black,
+        }
+    }
+
+    pub fn odd_regions(&self) -> usize {
+        self.regions.iter().filter(|region| region.len() % 2 == 1).count()
+    }
+}
+
+fn square_at(board: &Board, (row, col): (usize, usize)) -> BoardSquare {
+    board.get_coord_square_at(Coord::from((row, col))).1
+}
+
+fn neighbours(board: &Board, coord: Coord) -> impl Iterator<Item = (Coord, BoardSquare)> + '_ {
+    NEIGHBOURS.iter()
+        .map(move |vector| board.get_coord_square_towards(coord, Vector::from(*vector), 1))
+        .filter(|(_, square)| *square != BoardSquare::OutOfBounds)
+}
+
+fn player_features(board: &Board, game: &Game, piece: Piece) -> PlayerFeatures {
+    let count = |squares: &[(usize, usize)]| squares.iter().filter(|square| square_at(board, **square) == BoardSquare::Played(piece)).count();
+    let mut features = PlayerFeatures {
+        discs: board.count_pieces(piece),
+        mobility: game.valid_moves_for(piece).len(),
+        corners: count(&CORNERS),
+        x_squares: count(&X_SQUARES),
+        c_squares: count(&C_SQUARES),
+        stable: board.stable_discs(piece).len(),
+        ..PlayerFeatures::default()
+    };
+
+    for coord in Board::coords() {
+        match board.get_coord_square_at(coord).1 {
+            BoardSquare::Unplayed if neighbours(board, coord).any(|(_, square)| square == BoardSquare::Played(piece.opponent())) => {
+                features.potential_mobility += 1;
+            }
+            BoardSquare::Played(owner) if owner == piece && neighbours(board, coord).any(|(_, square)| square == BoardSquare::Unplayed) => {
+                features.frontier += 1;
+            }
+            _ => (),
+        }
+    }
+
+    features
+}
+
+fn empty_regions(board: &Board) -> Vec<Vec<Coord>> {
+    let mut seen = [[false; Board::BOARD_SIZE]; Board::BOARD_SIZE];
+    let mut regions = Vec::new();
+
+    for start in Board::coords() {
+        if seen[start.row][start.col] || board.get_coord_square_at(start).1 != BoardSquare::Unplayed { continue; }
+
+        seen[start.row][start.col] = true;
+        let mut region = vec![start];
+        let mut next = 0;
+        while next < region.len() {
+            for (coord, square) in neighbours(board, region[next]) {
+                if square == BoardSquare::Unplayed && !seen[coord.row][coord.col] {
+                    seen[coord.row][coord.col] = true;
+                    region.push(coord);
+                }
+            }
+            next += 1;
+        }
+
+        region.sort_by_key(|coord| (coord.row, coord.col));
+        regions.push(region);
+    }
+
+    regions
+}
+
+fn edge(board: &Board, square: impl Fn(usize) -> (usize, usize)) -> Edge {
+    let mut squares = [BoardSquare::Unplayed; Board::BOARD_SIZE];
+    for (index, edge_square) in squares.iter_mut().enumerate() {
+        *edge_square = square_at(board, square(index));
+    }
+
+    Edge { squares }
+}
+
+impl fmt::Display for Features {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (white, black) = (&self.white, &self.black);
+        let rows = [
+            ("Discs", white.discs, black.discs),
+            ("Mobility", white.mobility, black.mobility),
+            ("Potential mobility", white.potential_mobility, black.potential_mobility),
+            ("Frontier discs", white.frontier, black.frontier),
+            ("Corners", white.corners, black.corners),
+            ("X-squares", white.x_squares, black.x_squares),
+            ("C-squares", white.c_squares, black.c_squares),
+            ("Stable discs", white.stable, black.stable),
+        ];
+
+        writeln!(f, "{:<20}{:>4}{:>4}", "", "W", "B")?;
+        for (name, white, black) in rows.iter() {
+            writeln!(f, "{:<20}{:>4}{:>4}", name, white, black)?;
+        }
+
+        writeln!(f, "Empty regions: {} ({} odd)", self.regions.len(), self.odd_regions())?;
+        write!(f, "Edges: 1 {} | 8 {} | a {} | h {}", self.edges[0], self.edges[1], self.edges[2], self.edges[3])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Features;
+    use crate::reversi::board::Board;
+    use crate::reversi::coord::Coord;
+    use crate::reversi::game::Game;
+    use crate::reversi::piece::Piece;
+
+    #[test]
+    fn starting_position_is_balanced() {
+        let features = Features::of(&Board::new());
+
+        assert_eq!(features.white, features.black);
+        assert_eq!(features.white.mobility, 4);
+        assert_eq!(features.white.potential_mobility, 10);
+        assert_eq!(features.white.frontier, 2);
+        assert_eq!(features.regions.len(), 1);
+        assert_eq!(features.odd_regions(), 0);
+        assert!(features.edges.iter().all(|edge| edge.index() == 0));
+    }
+
+    #[test]
+    fn features_follow_the_moves() {
+        let moves: Vec<Coord> = ["d3", "c5", "f6"].iter().map(|text| text.parse().unwrap()).collect();
+        let game = Game::replay(Board::new(), Piece::White, &moves).unwrap();
+
+        let features = Features::of(game.board());
+
+        assert_eq!(features.white.discs, game.board().count_pieces(Piece::White));
+        assert_eq!(features.get(Piece::Black).mobility, game.valid_moves_for(Piece::Black).len());
+        assert_eq!(features.black.mobility, game.current_turn().valid_moves.len());
+    }
+
+    #[test]
+    fn corners_edges_and_regions_are_found() {
+        let mut board = Board::new();
+        board.set_squares(&vec![(0, 0).into(), (0, 1).into(), (1, 1).into()], Piece::White);
+        board.set_squares(&vec![(0, 7).into()], Piece::Black);
+        board.set_squares(&vec![(7, 1).into(), (6, 0).into(), (6, 1).into()], Piece::White);
+
+        let features = Features::of(&board);
+
+        assert_eq!((features.white.corners, features.white.x_squares, features.white.c_squares), (1, 2, 3));
+        assert_eq!((features.black.corners, features.black.stable), (1, 1));
+        assert_eq!(features.edges[0].to_string(), "WW.....B");
+        assert_eq!(features.edges[0].index(), (2 * 3 + 2) * 3usize.pow(6) + 1);
+        assert_eq!(features.regions.iter().map(Vec::len).sum::<usize>(), 64 - 11);
+        assert_eq!(features.regions.len(), 2); // a8 is cut off by a7, b7 and b8
+        assert_eq!(features.odd_regions(), 1);
+    }
+}
@@ -1,16 +1,22 @@
+use std::cmp::Ordering;
 use std::fmt;
+use std::sync::Arc;
 
 use crate::reversi::board::Board;
 use crate::reversi::coord::{Coord, Vector};
 use crate::reversi::piece::*;
 use crate::reversi::move_result::*;
+use crate::reversi::rules::Rules;
 
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Turn {
     pub player: Piece,
-    pub valid_moves: Vec<PositionalOutcome>,
+    pub valid_moves: Arc<[PositionalOutcome]>, // shared between copies of the game, since it's replaced rather than changed
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GameState {
     New,
     Played,
@@ -18,30 +24,135 @@ pub enum GameState {
     GameOver,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameResult {
+    Win(Piece),
+    WinOnTime(Piece),
+    Draw,
+}
+
+#[derive(Clone)]
 pub struct Game {
     board: Board,
     current_turn: Turn,
     state: GameState,
+    initial_board: Board,
+    initial_player: Piece,
+    history: Arc<Vec<Coord>>, // shared between copies of the game until one of them plays
+    last_outcome: Option<PositionalOutcome>,
+    lost_on_time: Option<Piece>,
+    rules: Rules,
 }
 
 impl Game {
     pub fn new() -> Game {
+        Game::from_position(Board::new(), Piece::White)
+    }
+
+    // A new game from the rules' starting position
+    pub fn with_rules(rules: Rules) -> Game {
+        let (board, player) = rules.start.position();
+        Game::from_position_with_rules(board, player, rules)
+    }
+
+    // Starts a game from an arbitrary position; if the given player has no
+    // valid moves, the turn is passed just like it would be after a play
+    pub fn from_position(board: Board, player: Piece) -> Game {
+        Game::from_position_with_rules(board, player, Rules::default())
+    }
+
+    pub fn from_position_with_rules(board: Board, player: Piece, rules: Rules) -> Game {
         // Initialize game
         let mut game = Game {
-            board: Board::new(),
-            current_turn: Turn { player: Piece::White, valid_moves: Vec::new() },
+            initial_board: board,
+            initial_player: player,
+            board,
+            current_turn: Turn { player, valid_moves: Arc::new([]) },
             state: GameState::New,
+            history: Arc::default(),
+            last_outcome: None,
+            lost_on_time: None,
+            rules,
         };
         
         // Advance to next turn knows how to handle a new game
         game.advance_to_next_turn();
+        game.pass_if_no_valid_moves();
         game
     }
 
+    // Rebuilds a game by playing every move from the given position, failing
+    // on the first move that isn't valid along with its index
+    pub fn replay(board: Board, player: Piece, moves: &[Coord]) -> Result<Game, (usize, PlayError)> {
+        Game::replay_with_rules(board, player, moves, Rules::default())
+    }
+
+    pub fn replay_with_rules(board: Board, player: Piece, moves: &[Coord], rules: Rules) -> Result<Game, (usize, PlayError)> {
+        let mut game = Game::from_position_with_rules(board, player, rules);
+
+        for (index, coord) in moves.iter().enumerate() {
+            game.try_play(*coord).map_err(|err| (index, err))?;
+        }
+
+        Ok(game)
+    }
+
     pub fn current_turn(&self) -> &Turn {
         &self.current_turn
     }
 
+    // The moves a player could make on the current board, whether or not it's
+    // their turn
+    pub fn valid_moves_for(&self, player: Piece) -> Vec<PositionalOutcome> {
+        self.calculate_valid_moves_for(player)
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    // Hash of the board and the player to move, for detecting repeated or
+    // diverging positions
+    pub fn position_hash(&self) -> u64 {
+        match self.current_turn.player {
+            Piece::White => self.board.hash(),
+            Piece::Black => !self.board.hash(),
+        }
+    }
+
+    pub fn initial_board(&self) -> &Board {
+        &self.initial_board
+    }
+
+    pub fn initial_player(&self) -> Piece {
+        self.initial_player
+    }
+
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    // The game as it was before any move, under the same rules
+    pub fn restart(&self) -> Game {
+        Game::from_position_with_rules(self.initial_board, self.initial_player, self.rules)
+    }
+
+    // Only the played coords are kept, since passes are forced and can be
+    // recovered by replaying the moves from the initial position
+    pub fn history(&self) -> &[Coord] {
+        &self.history
+    }
+
+    // The last move played and the discs it flipped
+    pub fn last_outcome(&self) -> Option<&PositionalOutcome> {
+        self.last_outcome.as_ref()
+    }
+
     fn check_move_for(&self, player: Piece, at_coord: Coord) -> MoveResult {
         let (_, square) = self.board.get_coord_square_at(at_coord);
         match square {
@@ -67,6 +178,44 @@ impl Game {
         }
     }
 
+    // Ends the game with a loss for the given player, regardless of the board
+    pub fn lose_on_time(&mut self, player: Piece) -> Result<(), PlayError> {
+        if self.state == GameState::GameOver { return Err(PlayError::GameOver); }
+
+        self.lost_on_time = Some(player);
+        self.state = GameState::GameOver;
+        Ok(())
+    }
+
+    pub fn result(&self) -> Option<GameResult> {
+        if self.state != GameState::GameOver { return None; }
+
+        if let Some(player) = self.lost_on_time {
+            return Some(GameResult::WinOnTime(player.opponent()));
+        }
+
+        Some(match self.rules.margin(&self.board, Piece::White).cmp(&0) {
+            Ordering::Greater => GameResult::Win(Piece::White),
+            Ordering::Less => GameResult::Win(Piece::Black),
+            Ordering::Equal => GameResult::Draw,
+        })
+    }
+
+    // Takes back the last move by replaying the others, which also restores any
+    // pass it caused; a loss on time is taken back along with it
+    pub fn undo(&mut self) -> Option<Coord> {
+        let (last, moves) = self.history.split_last()?;
+        let last = *last;
+
+        let mut game = self.restart();
+        for coord in moves {
+            game.try_play(*coord).expect("game history contains only valid moves");
+        }
+
+        *self = game;
+        Some(last)
+    }
+
     pub fn try_play(&mut self, move_coord: Coord) -> Result<(), PlayError> {
         if self.state == GameState::GameOver { return Err(PlayError::GameOver); }
 
@@ -81,20 +230,26 @@ impl Game {
         let mut coords_to_flip = confirmed_valid_move.changed_coords().clone(); // get pre-calculated coords to flip from valid play
         coords_to_flip.push(*confirmed_valid_move.coord()); // add the play itself; maybe this should already be inside the coords to flip
         self.board.set_squares(&coords_to_flip, self.current_turn.player);
+        self.last_outcome = Some(confirmed_valid_move.clone());
+        Arc::make_mut(&mut self.history).push(move_coord);
         self.state = GameState::Played;
         self.advance_to_next_turn();
+        self.pass_if_no_valid_moves();
 
-        if self.current_turn_has_valid_moves() { return Ok(()); }
+        Ok(())
+    }
+
+    fn pass_if_no_valid_moves(&mut self) {
+        if self.current_turn_has_valid_moves() { return; }
 
         // Else, process another turn
         self.state = GameState::PlayedAndPassed;
         self.advance_to_next_turn();
         
-        if self.current_turn_has_valid_moves() { return Ok(()); }
+        if self.current_turn_has_valid_moves() { return; }
 
-        // Else return GameOver
+        // Else it's GameOver
         self.state = GameState::GameOver;
-        Ok(())
     }
 
     fn advance_to_next_turn(&mut self) {
@@ -108,7 +263,7 @@ impl Game {
 
         self.current_turn = Turn {
             player: next_turn_player,
-            valid_moves: self.calculate_valid_moves_for(next_turn_player),
+            valid_moves: self.calculate_valid_moves_for(next_turn_player).into(),
         };
     }
 
@@ -117,14 +272,19 @@ impl Game {
     }
 
     fn calculate_valid_moves_for(&self, player: Piece) -> Vec<PositionalOutcome> {
+        // With the original start, the center is filled first, without flipping anything
+        let free_squares = self.rules.free_squares(&self.board);
+        if !free_squares.is_empty() {
+            return free_squares.into_iter().map(|coord| PositionalOutcome::new(coord, Vec::new())).collect();
+        }
+
         let mut valid_moves = Vec::new();
 
         // for every square in the board, check if playing that move as the current player is possible and cache it in a list of valid moves
         for row in 1..=Board::BOARD_SIZE {
             for column in 1..=Board::BOARD_SIZE {
-                match self.check_move_for(player, (row - 1, column - 1).into()) {
-                    MoveResult::Valid(_move) => valid_moves.push(_move),
-                    _ => (), // don't add the invalid plays
+                if let MoveResult::Valid(_move) = self.check_move_for(player, (row - 1, column - 1).into()) {
+                    valid_moves.push(_move); // don't add the invalid plays
                 }
             }
         }
@@ -164,6 +324,12 @@ impl Game {
     }
 }
 
+impl Default for Game {
+    fn default() -> Self {
+        Game::new()
+    }
+}
+
 impl fmt::Display for Game {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "{}", self.board)?;
@@ -172,11 +338,109 @@ impl fmt::Display for Game {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for Game {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("Game", 8)?;
+        state.serialize_field("board", &self.board)?;
+        state.serialize_field("current_turn", &self.current_turn)?;
+        state.serialize_field("state", &self.state)?;
+        state.serialize_field("initial_board", &self.initial_board)?;
+        state.serialize_field("initial_player", &self.initial_player)?;
+        state.serialize_field("history", &*self.history)?;
+        state.serialize_field("lost_on_time", &self.lost_on_time)?;
+        state.serialize_field("rules", &self.rules)?;
+        state.end()
+    }
+}
+
+// Valid moves are never trusted from the input: they are recalculated from the
+// board, and the stored state must agree with them. When the history is
+// present, the game is replayed from its initial position instead, and the
+// result must match the stored board
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Game {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Game, D::Error> {
+        use serde::de::Error;
+
+        #[derive(serde::Deserialize)]
+        struct SerializedTurn {
+            player: Piece,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct SerializedGame {
+            board: Board,
+            current_turn: SerializedTurn,
+            state: GameState,
+            initial_board: Option<Board>,
+            initial_player: Option<Piece>,
+            #[serde(default)]
+            history: Vec<Coord>,
+            #[serde(default)]
+            lost_on_time: Option<Piece>,
+            #[serde(default)]
+            rules: Rules,
+        }
+
+        let serialized = SerializedGame::deserialize(deserializer)?;
+
+        if let (Some(initial_board), Some(initial_player)) = (serialized.initial_board, serialized.initial_player) {
+            let mut game = Game::from_position_with_rules(initial_board, initial_player, serialized.rules);
+            for (index, coord) in serialized.history.iter().enumerate() {
+                game.try_play(*coord).map_err(|err| D::Error::custom(format!("move {} in history: {}", index + 1, err)))?;
+            }
+
+            if let Some(player) = serialized.lost_on_time {
+                game.lose_on_time(player).map_err(|_| D::Error::custom("game was over before the loss on time"))?;
+            }
+
+            if game.board != serialized.board
+                || game.current_turn.player != serialized.current_turn.player
+                || game.state != serialized.state
+            {
+                return Err(D::Error::custom("game does not match the result of replaying its history"));
+            }
+
+            return Ok(game);
+        }
+
+        let mut game = Game {
+            initial_board: serialized.board,
+            initial_player: serialized.current_turn.player,
+            board: serialized.board,
+            current_turn: Turn { player: serialized.current_turn.player, valid_moves: Arc::new([]) },
+            state: serialized.state,
+            history: Arc::default(),
+            last_outcome: None,
+            lost_on_time: serialized.lost_on_time,
+            rules: serialized.rules,
+        };
+        game.current_turn.valid_moves = game.calculate_valid_moves_for(game.current_turn.player).into();
+
+        let opponent_has_valid_moves = !game.calculate_valid_moves_for(game.current_turn.player.opponent()).is_empty();
+        match game.state {
+            GameState::GameOver if game.lost_on_time.is_some() => Ok(game),
+            _ if game.lost_on_time.is_some() => Err(D::Error::custom("game lost on time must be over")),
+            GameState::GameOver if game.current_turn_has_valid_moves() || opponent_has_valid_moves => {
+                Err(D::Error::custom("game is marked as over but there are still valid moves"))
+            }
+            GameState::New | GameState::Played | GameState::PlayedAndPassed if !game.current_turn_has_valid_moves() => {
+                Err(D::Error::custom("current player has no valid moves in a game that is not over"))
+            }
+            _ => Ok(game),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum PlayError {
     InvalidMove,
     OutOfBounds,
     GameOver,
+    OutOfTime,
 }
 
 impl fmt::Display for PlayError {
@@ -185,13 +449,15 @@ impl fmt::Display for PlayError {
             PlayError::InvalidMove => write!(f, "Invalid move"),
             PlayError::OutOfBounds => write!(f, "Out of bounds"),
             PlayError::GameOver => write!(f, "Game is over"),
+            PlayError::OutOfTime => write!(f, "Out of time"),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{BoardSquare, Game, Piece, MoveResult, PositionalOutcome, Turn};
+    use super::{Board, BoardSquare, Game, GameResult, Piece, MoveResult, PlayError, PositionalOutcome, Rules, Turn};
+    use std::sync::Arc;
     use crate::reversi::{coord::Coord, game::GameState};
 
     #[test]
@@ -300,7 +566,7 @@ mod tests {
         let next_turn_player = Piece::White;
         game.current_turn = Turn {
             player: next_turn_player,
-            valid_moves: game.calculate_valid_moves_for(next_turn_player),
+            valid_moves: game.calculate_valid_moves_for(next_turn_player).into(),
         };
 
         let corner_move = game
@@ -334,6 +600,112 @@ mod tests {
         assert_eq!(*game.current_turn.valid_moves[2].coord(), Coord::from((4, 2)));
     }
 
+    #[test]
+    fn played_moves_are_recorded_in_history() {
+        let mut game = Game::new();
+
+        assert!(game.try_play((2, 3).into()).is_ok());
+        assert!(game.try_play((0, 0).into()).is_err());
+        assert!(game.try_play((2, 2).into()).is_ok());
+
+        assert_eq!(game.history(), &[Coord::from((2, 3)), Coord::from((2, 2))]);
+        assert_eq!(game.last_outcome(), Some(&PositionalOutcome::new((2, 2).into(), vec![(3, 3).into()])));
+    }
+
+    #[test]
+    fn replaying_history_rebuilds_the_same_game() {
+        let mut game = Game::new();
+        assert!(game.try_play((2, 3).into()).is_ok());
+        assert!(game.try_play((2, 2).into()).is_ok());
+
+        let replayed = Game::replay(*game.initial_board(), game.initial_player(), game.history()).unwrap();
+
+        assert_eq!(replayed.board, game.board);
+        assert_eq!(replayed.state, game.state);
+        assert_eq!(replayed.current_turn.player, game.current_turn.player);
+        assert_eq!(replayed.current_turn.valid_moves, game.current_turn.valid_moves);
+    }
+
+    #[test]
+    fn copies_share_history_until_one_plays() {
+        let mut game = Game::new();
+        game.try_play("d3".parse().unwrap()).unwrap();
+
+        let mut copy = game.clone();
+        assert!(Arc::ptr_eq(&copy.history, &game.history));
+        assert!(Arc::ptr_eq(&copy.current_turn.valid_moves, &game.current_turn.valid_moves));
+
+        copy.try_play("c5".parse().unwrap()).unwrap();
+        assert_eq!(game.history().len(), 1);
+        assert_eq!(copy.history().len(), 2);
+        assert_eq!(copy.board().count_pieces(Piece::Black), 3);
+        assert_eq!(game.board().count_pieces(Piece::Black), 1);
+    }
+
+    #[test]
+    fn undo_takes_back_the_last_move() {
+        let mut game = Game::new();
+        assert_eq!(game.undo(), None);
+
+        for coord in ["d3", "c5"] {
+            assert!(game.try_play(coord.parse().unwrap()).is_ok());
+        }
+        let before_last_move = *game.board();
+        assert!(game.try_play("f6".parse().unwrap()).is_ok());
+
+        assert_eq!(game.undo(), Some("f6".parse().unwrap()));
+        assert_eq!(game.board(), &before_last_move);
+        assert_eq!(game.history().len(), 2);
+        assert_eq!(game.current_turn().player, Piece::White);
+        assert_eq!(game.last_outcome().map(|outcome| *outcome.coord()), Some("c5".parse().unwrap()));
+    }
+
+    #[test]
+    fn replaying_invalid_move_reports_its_index() {
+        let result = Game::replay(Board::new(), Piece::White, &[(2, 3).into(), (0, 0).into()]);
+
+        assert!(matches!(result, Err((1, PlayError::InvalidMove))));
+    }
+
+    #[test]
+    fn starting_from_position_passes_when_player_has_no_moves() {
+        // Same position as the pass test after White's play: Black only at (1,1)
+        // and empty only at (0,1), so Black cannot play but White can
+        let mut board = Board::new();
+        let white: Vec<Coord> = (0..8)
+            .flat_map(|r| (0..8).map(move |c| (r, c).into()))
+            .filter(|pos| *pos != Coord::from((1, 1)) && *pos != Coord::from((0, 1)))
+            .collect();
+        board.set_squares(&white, Piece::White);
+        board.set_squares(&vec![(1, 1).into()], Piece::Black);
+
+        let game = Game::from_position(board, Piece::Black);
+
+        assert_eq!(game.current_turn.player, Piece::White);
+        assert_eq!(game.state, GameState::PlayedAndPassed);
+    }
+
+    #[test]
+    fn game_can_be_shared_between_threads() {
+        fn assert_send_sync<T: Send + Sync + Clone>() {}
+
+        assert_send_sync::<Game>();
+        assert_send_sync::<Board>();
+    }
+
+    #[test]
+    fn losing_on_time_ends_the_game() {
+        let mut game = Game::new();
+        assert_eq!(game.result(), None);
+
+        assert!(game.lose_on_time(Piece::White).is_ok());
+
+        assert_eq!(game.state, GameState::GameOver);
+        assert_eq!(game.result(), Some(GameResult::WinOnTime(Piece::Black)));
+        assert!(matches!(game.try_play((2, 3).into()), Err(PlayError::GameOver)));
+        assert!(game.lose_on_time(Piece::Black).is_err());
+    }
+
     /// Creates a Game where all squares are White except for the given
     /// black and empty positions. Current player is set to White with
     /// available positions already calculated.
@@ -354,7 +726,7 @@ mod tests {
         let next_turn_player = Piece::White;
         game.current_turn = Turn {
             player: next_turn_player,
-            valid_moves: game.calculate_valid_moves_for(next_turn_player),
+            valid_moves: game.calculate_valid_moves_for(next_turn_player).into(),
         };
 
         game
@@ -419,5 +791,84 @@ mod tests {
         assert!(result.is_ok());
 
         assert_eq!(game.state, GameState::GameOver);
+        assert_eq!(game.result(), Some(GameResult::Win(Piece::White)));
+    }
+
+    #[test]
+    fn fewest_discs_win_under_misere_rules() {
+        let mut board = Board::empty();
+        board.set_squares(&vec![(0, 0).into(), (0, 1).into()], Piece::White);
+        board.set_squares(&vec![(7, 7).into()], Piece::Black);
+
+        let standard = Game::from_position(board, Piece::White);
+        let mut misere = Game::from_position_with_rules(board, Piece::White, Rules::misere());
+
+        assert_eq!(standard.result(), Some(GameResult::Win(Piece::White)));
+        assert_eq!(misere.result(), Some(GameResult::Win(Piece::Black)));
+        assert_eq!(misere.restart().rules(), Rules::misere());
+        assert!(misere.undo().is_none());
+    }
+
+    #[test]
+    fn replays_keep_their_rules() {
+        let game = generate_endgame(
+            &[(0, 0).into(), (7, 5).into()],
+            &[(0, 1).into(), (7, 6).into()],
+        );
+
+        let replayed = Game::replay_with_rules(*game.board(), Piece::White, &[(7, 6).into()], Rules::misere()).unwrap();
+
+        assert_eq!(replayed.rules(), Rules::misere());
+        assert_eq!(replayed.result(), Some(GameResult::Win(Piece::Black)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn game_roundtrips_through_json() {
+        let mut game = Game::new();
+        assert!(game.try_play((3, 2).into()).is_ok());
+
+        let json = serde_json::to_string(&game).unwrap();
+        let restored: Game = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.state, GameState::Played);
+        assert_eq!(restored.current_turn.player, Piece::Black);
+        assert_eq!(restored.current_turn.valid_moves, game.current_turn.valid_moves);
+        assert_eq!(restored.to_string(), game.to_string());
+        assert_eq!(restored.history, game.history);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserializing_game_with_tampered_history_fails() {
+        let mut game = Game::new();
+        assert!(game.try_play((3, 2).into()).is_ok());
+
+        let mut value = serde_json::to_value(&game).unwrap();
+        value["history"] = serde_json::json!([{ "row": 2, "col": 3 }]);
+
+        assert!(serde_json::from_value::<Game>(value).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserializing_game_recalculates_valid_moves() {
+        let mut value = serde_json::to_value(Game::new()).unwrap();
+        value["current_turn"]["valid_moves"] = serde_json::json!([]);
+        value.as_object_mut().unwrap().remove("history");
+
+        let restored: Game = serde_json::from_value(value).unwrap();
+
+        assert_eq!(restored.current_turn.valid_moves.len(), 4);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserializing_inconsistent_game_fails() {
+        let mut value = serde_json::to_value(Game::new()).unwrap();
+        value["state"] = serde_json::json!("GameOver");
+        value.as_object_mut().unwrap().remove("initial_board");
+
+        assert!(serde_json::from_value::<Game>(value).is_err());
     }
 }
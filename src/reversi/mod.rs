@@ -1,5 +1,26 @@
+pub mod analysis;
 pub mod board;
+pub mod clock;
 pub mod coord;
+pub mod driver;
+pub mod engine;
+pub mod environment;
+pub mod features;
 pub mod game;
 pub mod piece;
-pub mod move_result;
\ No newline at end of file
+pub mod planes;
+pub mod puzzle;
+pub mod random;
+#[cfg(feature = "raster")]
+pub mod raster;
+pub mod render;
+pub mod rules;
+pub mod move_result;
+pub mod neural;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod network;
+pub mod save;
+pub mod svg;
+pub mod symmetry;
+pub mod time_manager;
+pub mod transposition;
\ No newline at end of file
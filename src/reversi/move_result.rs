@@ -1,13 +1,15 @@
 use crate::reversi::coord::Coord;
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MoveResult {
     Valid(PositionalOutcome),
     Invalid,
     OutOfBounds,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PositionalOutcome {
     played_coord: Coord,
     changed_coords: Vec<Coord>,
@@ -17,7 +19,7 @@ impl PositionalOutcome {
     pub fn new(played_coord: Coord, changed_coords: Vec<Coord>) -> PositionalOutcome {
         PositionalOutcome {
             played_coord,
-            changed_coords: changed_coords,
+            changed_coords,
         }
     }
 
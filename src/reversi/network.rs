@@ -0,0 +1,260 @@
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+
+use crate::reversi::coord::Coord;
+use crate::reversi::driver::MatchDriver;
+use crate::reversi::game::PlayError;
+use crate::reversi::rules::Rules;
+
+pub const PROTOCOL_VERSION: u32 = 2;
+
+// The messages peers exchange, one per line:
+//   hello <version> <position hash> <rules> <name>   sent by both sides on connecting
+//   move <coord> <position hash>                     a move and the position it led to
+//   quit                                             the sender left the game
+// The rules' words are joined with commas, like misere,parallel, so they stay
+// one word.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NetMessage {
+    Hello { version: u32, hash: u64, rules: Rules, name: String },
+    Move { coord: Coord, hash: u64 },
+    Quit,
+}
+
+impl fmt::Display for NetMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NetMessage::Hello { version, hash, rules, name } => write!(f, "hello {} {:016x} {} {}", version, hash, rules.to_string().replace(' ', ","), name),
+            NetMessage::Move { coord, hash } => write!(f, "move {} {:016x}", coord, hash),
+            NetMessage::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+impl FromStr for NetMessage {
+    type Err = NetworkError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let invalid = || NetworkError::Protocol(format!("Invalid message: {}", line));
+        let parse_hash = |text: Option<&str>| text.and_then(|text| u64::from_str_radix(text, 16).ok()).ok_or_else(invalid);
+
+        let mut words = line.trim_end().splitn(5, ' ');
+        match words.next() {
+            Some("hello") => {
+                let version = words.next().and_then(|text| text.parse().ok()).ok_or_else(invalid)?;
+                let hash = parse_hash(words.next())?;
+                let rules = words.next().and_then(|text| text.replace(',', " ").parse().ok()).ok_or_else(invalid)?;
+                let name = words.next().filter(|name| !name.is_empty()).ok_or_else(invalid)?;
+                Ok(NetMessage::Hello { version, hash, rules, name: name.to_string() })
+            }
+            Some("move") => {
+                let coord = words.next().and_then(|text| text.parse().ok()).ok_or_else(invalid)?;
+                let hash = parse_hash(words.next())?;
+                match words.next() {
+                    None => Ok(NetMessage::Move { coord, hash }),
+                    Some(_) => Err(invalid()),
+                }
+            }
+            Some("quit") => Ok(NetMessage::Quit),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum NetworkError {
+    Io(io::Error),
+    Closed,
+    Protocol(String),
+    IllegalMove { coord: Coord, error: PlayError },
+    Desync { coord: Coord }, // the peer reached a different position after this move
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NetworkError::Io(err) => write!(f, "Connection error: {}", err),
+            NetworkError::Closed => write!(f, "The other player closed the connection"),
+            NetworkError::Protocol(message) => write!(f, "{}", message),
+            NetworkError::IllegalMove { coord, error } => write!(f, "The other player sent an illegal move ({}): {}", coord, error),
+            NetworkError::Desync { coord } => write!(f, "The games went out of sync after {}", coord),
+        }
+    }
+}
+
+impl From<io::Error> for NetworkError {
+    fn from(err: io::Error) -> Self {
+        NetworkError::Io(err)
+    }
+}
+
+// A line-based connection to the other player
+pub struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> Result<Connection, NetworkError> {
+        let writer = stream.try_clone()?;
+        Ok(Connection { reader: BufReader::new(stream), writer })
+    }
+
+    // Waits for the other player to join
+    pub fn accept(listener: &TcpListener) -> Result<Connection, NetworkError> {
+        let (stream, _) = listener.accept()?;
+        Connection::new(stream)
+    }
+
+    pub fn connect<A: ToSocketAddrs>(address: A) -> Result<Connection, NetworkError> {
+        Connection::new(TcpStream::connect(address)?)
+    }
+
+    pub fn send(&mut self, message: &NetMessage) -> Result<(), NetworkError> {
+        writeln!(self.writer, "{}", message)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    pub fn receive(&mut self) -> Result<NetMessage, NetworkError> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line)? {
+            0 => Err(NetworkError::Closed),
+            _ => line.parse(),
+        }
+    }
+
+    // Introduces both players and checks they're starting from the same
+    // position under the same rules, returning the other player's name
+    pub fn handshake(&mut self, name: &str, hash: u64, rules: Rules) -> Result<String, NetworkError> {
+        // Names end the line, so only the line break has to go
+        let name = name.replace(['\r', '\n'], " ");
+        self.send(&NetMessage::Hello { version: PROTOCOL_VERSION, hash, rules, name })?;
+
+        match self.receive()? {
+            NetMessage::Hello { version, .. } if version != PROTOCOL_VERSION => {
+                Err(NetworkError::Protocol(format!("The other player uses protocol version {}, but this is version {}", version, PROTOCOL_VERSION)))
+            }
+            NetMessage::Hello { rules: peer_rules, .. } if peer_rules != rules => {
+                Err(NetworkError::Protocol(format!("The other player is playing with {} rules, but this game uses {}", peer_rules, rules)))
+            }
+            NetMessage::Hello { hash: peer_hash, .. } if peer_hash != hash => {
+                Err(NetworkError::Protocol(String::from("The other player is starting from a different position")))
+            }
+            NetMessage::Hello { name, .. } => Ok(name),
+            message => Err(NetworkError::Protocol(format!("Expected a greeting, got: {}", message))),
+        }
+    }
+}
+
+// Plays the other player's move on our side, checking it leads to the same
+// position it did on theirs
+pub fn play_remote_move(driver: &mut MatchDriver, coord: Coord, hash: u64) -> Result<(), NetworkError> {
+    driver.play(coord).map_err(|error| NetworkError::IllegalMove { coord, error })?;
+
+    match driver.game().position_hash() == hash {
+        true => Ok(()),
+        false => Err(NetworkError::Desync { coord }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::{play_remote_move, Connection, NetMessage, NetworkError};
+    use crate::reversi::driver::MatchDriver;
+    use crate::reversi::game::{Game, PlayError};
+    use crate::reversi::rules::{Rules, Start};
+    use crate::reversi::save::{Players, SavedGame};
+
+    fn driver() -> MatchDriver {
+        MatchDriver::new(SavedGame::new(Players::default(), Game::new(), None))
+    }
+
+    #[test]
+    fn messages_round_trip_through_text() {
+        let messages = [
+            NetMessage::Hello { version: 1, hash: 0xdead_beef, rules: Rules::standard(), name: String::from("Ann Smith") },
+            NetMessage::Hello { version: 2, hash: 1, rules: Rules::misere().with_start(Start::Xot(3)), name: String::from("Bob") },
+            NetMessage::Move { coord: "d3".parse().unwrap(), hash: u64::MAX },
+            NetMessage::Quit,
+        ];
+
+        for message in messages.iter() {
+            assert_eq!(message.to_string().parse::<NetMessage>().unwrap(), *message);
+        }
+
+        assert!("move d3".parse::<NetMessage>().is_err());
+        assert!("move d3 xyz".parse::<NetMessage>().is_err());
+        assert!("hello 1 ff".parse::<NetMessage>().is_err());
+        assert!("hello 1 ff Bob".parse::<NetMessage>().is_err());
+        assert!("resign".parse::<NetMessage>().is_err());
+    }
+
+    #[test]
+    fn peers_exchange_names_and_moves() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let hash = Game::new().position_hash();
+
+        let joiner = thread::spawn(move || {
+            let mut connection = Connection::connect(address).unwrap();
+            let host_name = connection.handshake("Bob", hash, Rules::standard()).unwrap();
+            (host_name, connection.receive().unwrap())
+        });
+
+        let mut connection = Connection::accept(&listener).unwrap();
+        assert_eq!(connection.handshake("Alice", hash, Rules::standard()).unwrap(), "Bob");
+
+        let mut game = Game::new();
+        game.try_play("d3".parse().unwrap()).unwrap();
+        let sent = NetMessage::Move { coord: "d3".parse().unwrap(), hash: game.position_hash() };
+        connection.send(&sent).unwrap();
+
+        assert_eq!(joiner.join().unwrap(), (String::from("Alice"), sent));
+    }
+
+    #[test]
+    fn handshake_fails_on_different_positions() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let joiner = thread::spawn(move || Connection::connect(address).unwrap().handshake("Bob", 2, Rules::standard()).is_err());
+
+        assert!(Connection::accept(&listener).unwrap().handshake("Alice", 1, Rules::standard()).is_err());
+        assert!(joiner.join().unwrap());
+    }
+
+    #[test]
+    fn handshake_fails_on_different_rules() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let hash = Game::new().position_hash();
+
+        let joiner = thread::spawn(move || Connection::connect(address).unwrap().handshake("Bob", hash, Rules::misere()).is_err());
+
+        let result = Connection::accept(&listener).unwrap().handshake("Alice", hash, Rules::standard());
+        assert!(matches!(result, Err(NetworkError::Protocol(_))));
+        assert!(joiner.join().unwrap());
+    }
+
+    #[test]
+    fn remote_moves_are_checked_against_the_sent_hash() {
+        let mut expected = Game::new();
+        expected.try_play("d3".parse().unwrap()).unwrap();
+
+        let mut in_sync = driver();
+        assert!(play_remote_move(&mut in_sync, "d3".parse().unwrap(), expected.position_hash()).is_ok());
+
+        let mut out_of_sync = driver();
+        let result = play_remote_move(&mut out_of_sync, "d3".parse().unwrap(), !expected.position_hash());
+        assert!(matches!(result, Err(NetworkError::Desync { .. })));
+
+        let result = play_remote_move(&mut driver(), "a1".parse().unwrap(), 0);
+        assert!(matches!(result, Err(NetworkError::IllegalMove { error: PlayError::InvalidMove, .. })));
+    }
+}
@@ -0,0 +1,345 @@
+// Inference for small value and policy networks trained offline. Networks are
+// fully connected: the 128 inputs are the planes of the player to move (see
+// planes.rs), hidden layers use ReLU, and two heads share the last hidden
+// layer. The policy head gives a logit for each of the 65 actions of
+// environment.rs (the squares from a1 to h8, then passing), and the value head
+// goes through tanh, giving the expected result for the player to move, from
+// -1 for a loss to 1 for a win.
+//
+// Weights files are little-endian binary:
+//   magic     4 bytes          "RVNN"
+//   version   u32              1
+//   hidden    u32              the number of hidden layers, H
+//   sizes     H × u32          the size of each hidden layer
+//   layers    for each hidden layer, then the policy head and the value head:
+//               weights  outputs × inputs f32, one row of inputs per output
+//               biases   outputs f32
+
+use std::fmt;
+use std::io::{self, Read, Write};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{fs::File, io::{BufReader, BufWriter}, path::Path};
+
+use crate::reversi::board::Board;
+use crate::reversi::coord::Coord;
+use crate::reversi::engine::Evaluator;
+use crate::reversi::environment::{coord_to_action, ACTION_COUNT};
+use crate::reversi::game::{Game, GameResult, GameState};
+use crate::reversi::piece::Piece;
+use crate::reversi::planes::{self, PLANE_COUNT};
+use crate::reversi::random::Rng;
+
+const MAGIC: &[u8; 4] = b"RVNN";
+const VERSION: u32 = 1;
+const INPUT_SIZE: usize = PLANE_COUNT * Board::BOARD_SIZE * Board::BOARD_SIZE;
+
+// Limits that keep a corrupt file from allocating without bounds
+const MAX_HIDDEN_LAYERS: usize = 16;
+const MAX_LAYER_SIZE: usize = 4096;
+
+// A value of 1 is worth this much to the engine, well below a won game
+const VALUE_SCALE: f32 = 1000.0;
+
+#[derive(Debug)]
+pub enum NeuralError {
+    Io(io::Error),
+    Format(String),
+}
+
+impl fmt::Display for NeuralError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NeuralError::Io(err) => write!(f, "Could not read the weights: {}", err),
+            NeuralError::Format(message) => write!(f, "Invalid weights file: {}", message),
+        }
+    }
+}
+
+impl From<io::Error> for NeuralError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::UnexpectedEof => NeuralError::Format(String::from("the file ends early")),
+            _ => NeuralError::Io(err),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Layer {
+    inputs: usize,
+    weights: Vec<f32>,
+    biases: Vec<f32>,
+}
+
+impl Layer {
+    fn forward(&self, input: &[f32]) -> Vec<f32> {
+        self.weights
+            .chunks(self.inputs)
+            .zip(self.biases.iter())
+            .map(|(row, bias)| row.iter().zip(input).map(|(weight, x)| weight * x).sum::<f32>() + bias)
+            .collect()
+    }
+
+    fn read<R: Read>(reader: &mut R, inputs: usize, outputs: usize) -> io::Result<Layer> {
+        Ok(Layer { inputs, weights: read_floats(reader, inputs * outputs)?, biases: read_floats(reader, outputs)? })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for value in self.weights.iter().chain(self.biases.iter()) {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_floats<R: Read>(reader: &mut R, count: usize) -> io::Result<Vec<f32>> {
+    let mut bytes = vec![0; count * 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes.chunks(4).map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])).collect())
+}
+
+// What a network makes of a position: the expected result for the player to
+// move, and the probability of each action being the best
+#[derive(Clone, Debug, PartialEq)]
+pub struct Evaluation {
+    pub value: f32,
+    pub policy: [f32; ACTION_COUNT],
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Network {
+    hidden: Vec<Layer>,
+    policy: Layer,
+    value: Layer,
+}
+
+impl Network {
+    // A network with small random weights from a seed, to start training from
+    // or for testing
+    pub fn random(hidden_sizes: &[usize], seed: u64) -> Network {
+        let mut rng = Rng::new(seed);
+        let mut layer = |inputs: usize, outputs: usize| {
+            let scale = (2.0 / inputs as f32).sqrt();
+            let weights = (0..inputs * outputs).map(|_| ((rng.next_u64() >> 40) as f32 / (1u64 << 24) as f32 * 2.0 - 1.0) * scale).collect();
+            Layer { inputs, weights, biases: vec![0.0; outputs] }
+        };
+
+        let mut inputs = INPUT_SIZE;
+        let mut hidden = Vec::new();
+        for size in hidden_sizes {
+            hidden.push(layer(inputs, *size));
+            inputs = *size;
+        }
+
+        Network { hidden, policy: layer(inputs, ACTION_COUNT), value: layer(inputs, 1) }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<Path>) -> Result<Network, NeuralError> {
+        Network::read(&mut BufReader::new(File::open(path)?))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), NeuralError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn read<R: Read>(reader: &mut R) -> Result<Network, NeuralError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC { return Err(NeuralError::Format(String::from("not a network weights file"))); }
+
+        let version = read_u32(reader)?;
+        if version != VERSION { return Err(NeuralError::Format(format!("unsupported version {}", version))); }
+
+        let hidden_count = read_u32(reader)? as usize;
+        if hidden_count > MAX_HIDDEN_LAYERS {
+            return Err(NeuralError::Format(format!("{} hidden layers, at most {} are supported", hidden_count, MAX_HIDDEN_LAYERS)));
+        }
+
+        let mut sizes = Vec::with_capacity(hidden_count);
+        for _ in 0..hidden_count {
+            match read_u32(reader)? as usize {
+                size @ 1..=MAX_LAYER_SIZE => sizes.push(size),
+                size => return Err(NeuralError::Format(format!("hidden layer of size {}, expected 1 to {}", size, MAX_LAYER_SIZE))),
+            }
+        }
+
+        let mut inputs = INPUT_SIZE;
+        let mut hidden = Vec::with_capacity(hidden_count);
+        for size in sizes {
+            hidden.push(Layer::read(reader, inputs, size)?);
+            inputs = size;
+        }
+        let policy = Layer::read(reader, inputs, ACTION_COUNT)?;
+        let value = Layer::read(reader, inputs, 1)?;
+
+        match reader.read(&mut [0])? {
+            0 => Ok(Network { hidden, policy, value }),
+            _ => Err(NeuralError::Format(String::from("unexpected data after the weights"))),
+        }
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&(self.hidden.len() as u32).to_le_bytes())?;
+        for layer in self.hidden.iter() {
+            writer.write_all(&(layer.biases.len() as u32).to_le_bytes())?;
+        }
+
+        for layer in self.hidden.iter().chain([&self.policy, &self.value]) {
+            layer.write(writer)?;
+        }
+        Ok(())
+    }
+
+    // Evaluates a board with the given player to move, spreading the policy
+    // over every action, legal or not
+    pub fn predict_position(&self, board: &Board, player: Piece) -> Evaluation {
+        let mut activations: Vec<f32> = planes::encode(board, player).iter().flatten().flatten().copied().collect();
+        for layer in self.hidden.iter() {
+            activations = layer.forward(&activations).into_iter().map(|x| x.max(0.0)).collect();
+        }
+
+        let logits = self.policy.forward(&activations);
+        let mut policy = [0.0; ACTION_COUNT];
+        softmax(&logits, &mut policy);
+
+        Evaluation { value: self.value.forward(&activations)[0].tanh(), policy }
+    }
+
+    // Evaluates a game for the player to move, spreading the policy over
+    // their legal moves only. Finished games get their actual result and no
+    // policy at all, since no action is legal once the game is over.
+    pub fn predict(&self, game: &Game) -> Evaluation {
+        let player = game.current_turn().player;
+
+        if game.state() == GameState::GameOver {
+            let policy = [0.0; ACTION_COUNT];
+            let value = match game.result() {
+                Some(GameResult::Win(winner)) | Some(GameResult::WinOnTime(winner)) if winner == player => 1.0,
+                Some(GameResult::Win(_)) | Some(GameResult::WinOnTime(_)) => -1.0,
+                _ => 0.0,
+            };
+            return Evaluation { value, policy };
+        }
+
+        let mut evaluation = self.predict_position(game.board(), player);
+        let legal: Vec<usize> = game.current_turn().valid_moves.iter().map(|mv| coord_to_action(*mv.coord())).collect();
+        let total: f32 = legal.iter().map(|action| evaluation.policy[*action]).sum();
+
+        let mut policy = [0.0; ACTION_COUNT];
+        for action in legal.iter() {
+            policy[*action] = match total > 0.0 {
+                true => evaluation.policy[*action] / total,
+                false => 1.0 / legal.len() as f32,
+            };
+        }
+        evaluation.policy = policy;
+        evaluation
+    }
+}
+
+fn softmax(logits: &[f32], output: &mut [f32]) {
+    let max = logits.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    for (out, logit) in output.iter_mut().zip(logits) {
+        *out = (logit - max).exp();
+    }
+
+    let total: f32 = output.iter().sum();
+    for out in output.iter_mut() {
+        *out /= total;
+    }
+}
+
+// Lets the engine search with the network's value, and try the moves it
+// likes best first
+impl Evaluator for Network {
+    fn evaluate(&self, game: &Game, player: Piece) -> i32 {
+        let value = self.predict_position(game.board(), game.current_turn().player).value;
+        let score = (value * VALUE_SCALE).round() as i32;
+
+        match game.current_turn().player == player {
+            true => score,
+            false => -score,
+        }
+    }
+
+    fn priors(&self, game: &Game) -> Vec<(Coord, f32)> {
+        let policy = self.predict(game).policy;
+        game.current_turn().valid_moves.iter().map(|mv| (*mv.coord(), policy[coord_to_action(*mv.coord())])).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Network, NeuralError, INPUT_SIZE};
+    use crate::reversi::board::Board;
+    use crate::reversi::engine::{Engine, Evaluator, SearchLimits};
+    use crate::reversi::environment::{ACTION_COUNT, PASS_ACTION};
+    use crate::reversi::game::Game;
+    use crate::reversi::piece::Piece;
+
+    #[test]
+    fn weights_round_trip_through_the_file_format() {
+        let network = Network::random(&[16, 8], 1);
+        let mut bytes = Vec::new();
+        network.write(&mut bytes).unwrap();
+
+        let header = 4 + 4 + 4 + 2 * 4;
+        let floats = (INPUT_SIZE * 16 + 16) + (16 * 8 + 8) + (8 * ACTION_COUNT + ACTION_COUNT) + (8 + 1);
+        assert_eq!(bytes.len(), header + floats * 4);
+        assert_eq!(Network::read(&mut bytes.as_slice()).unwrap(), network);
+
+        assert!(matches!(Network::read(&mut &bytes[..bytes.len() - 1]), Err(NeuralError::Format(_))));
+        bytes.push(0);
+        assert!(matches!(Network::read(&mut bytes.as_slice()), Err(NeuralError::Format(_))));
+        assert!(matches!(Network::read(&mut &b"RVNN\x02\0\0\0"[..]), Err(NeuralError::Format(_))));
+    }
+
+    #[test]
+    fn predictions_are_probabilities_over_legal_moves() {
+        let network = Network::random(&[32], 2);
+        let game = Game::new();
+
+        let raw = network.predict_position(game.board(), Piece::White);
+        assert!((raw.policy.iter().sum::<f32>() - 1.0).abs() < 1e-4);
+        assert!(raw.value > -1.0 && raw.value < 1.0);
+
+        let evaluation = network.predict(&game);
+        assert!((evaluation.policy.iter().sum::<f32>() - 1.0).abs() < 1e-4);
+        assert_eq!(evaluation.policy.iter().filter(|prior| **prior > 0.0).count(), 4);
+        assert_eq!(evaluation.policy[PASS_ACTION], 0.0);
+        assert_eq!(network.priors(&game).len(), 4);
+
+        let mut board = Board::empty();
+        board.set_squares(&vec![(0, 0).into()], Piece::White);
+        let finished = network.predict(&Game::from_position(board, Piece::White));
+        assert_eq!(finished.value.abs(), 1.0);
+        assert!(finished.policy.iter().all(|prior| *prior == 0.0));
+    }
+
+    #[test]
+    fn the_engine_searches_with_the_network() {
+        let network = Network::random(&[8], 3);
+        let game = Game::from_position(Board::new(), Piece::Black);
+
+        let score = network.evaluate(&game, Piece::Black);
+        assert_eq!(network.evaluate(&game, Piece::White), -score);
+
+        let limits = SearchLimits { max_depth: 3, ..SearchLimits::default() };
+        let result = Engine::new(3).search_with(&game, &limits, &network);
+        assert!(game.current_turn().valid_moves.iter().any(|mv| Some(*mv.coord()) == result.best_move));
+        assert_eq!(result.depth, 3);
+    }
+}
@@ -1,6 +1,7 @@
 use std::fmt;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Piece {
     Black,
     White,
@@ -28,6 +29,7 @@ impl fmt::Display for Piece {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BoardSquare {
     Played(Piece),
     Unplayed,
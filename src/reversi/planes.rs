@@ -0,0 +1,45 @@
+use crate::reversi::board::Board;
+use crate::reversi::piece::{BoardSquare, Piece};
+
+pub const PLANE_COUNT: usize = 2;
+
+pub type Planes = [[[f32; Board::BOARD_SIZE]; Board::BOARD_SIZE]; PLANE_COUNT];
+
+// Encodes a board from one player's point of view, for machine learning: the
+// first plane has a 1 wherever that player has a disc and the second wherever
+// their opponent has one, indexed by row and then column
+pub fn encode(board: &Board, player: Piece) -> Planes {
+    let mut planes = [[[0.0; Board::BOARD_SIZE]; Board::BOARD_SIZE]; PLANE_COUNT];
+
+    for coord in Board::coords() {
+        let plane = match board.get_coord_square_at(coord) {
+            (_, BoardSquare::Played(piece)) if piece == player => 0,
+            (_, BoardSquare::Played(_)) => 1,
+            _ => continue,
+        };
+        planes[plane][coord.row][coord.col] = 1.0;
+    }
+
+    planes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encode;
+    use crate::reversi::board::Board;
+    use crate::reversi::piece::Piece;
+
+    #[test]
+    fn planes_are_relative_to_the_player() {
+        let board = Board::new();
+
+        let white = encode(&board, Piece::White);
+        let black = encode(&board, Piece::Black);
+
+        assert_eq!(white[0], black[1]);
+        assert_eq!(white[1], black[0]);
+        assert_eq!(white[0][3][4], 1.0); // e4
+        assert_eq!(white[1][3][3], 1.0); // d4
+        assert_eq!(white.iter().flatten().flatten().sum::<f32>(), 4.0);
+    }
+}
@@ -0,0 +1,328 @@
+use std::fmt;
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs;
+use std::io;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::reversi::board::Board;
+use crate::reversi::coord::Coord;
+use crate::reversi::engine::Engine;
+use crate::reversi::game::{Game, GameState, PlayError};
+use crate::reversi::piece::{BoardSquare, Piece};
+use crate::reversi::random::Rng;
+use crate::reversi::rules::Rules;
+
+// Self-played games open with this many random moves, so they don't all
+// follow the engine's favourite line
+const RANDOM_OPENING_MOVES: usize = 10;
+
+// A position where exactly one move wins for the player to move, as proven by
+// solving every move to the end of the game
+#[derive(Clone, Debug, PartialEq)]
+pub struct Puzzle {
+    pub board: Board,
+    pub player: Piece,
+    pub solution: Coord,
+    pub margin: i32, // the final disc difference after the solution, with perfect play, as it counts under the rules
+    pub rules: Rules,
+}
+
+impl Puzzle {
+    pub fn game(&self) -> Game {
+        Game::from_position_with_rules(self.board, self.player, self.rules)
+    }
+
+    // Plays the answer on the puzzle's position, so invalid moves are rejected
+    // like in a game, and tells whether it's the winning one
+    pub fn check(&self, answer: Coord) -> Result<bool, PlayError> {
+        self.game().try_play(answer)?;
+        Ok(answer == self.solution)
+    }
+}
+
+// Puzzles are written one per line, with the board row by row from row 1,
+// then the player to move, the solution and its margin, and the rules unless
+// they're standard, with their words joined by commas:
+//
+//     WWWWWWWW/WWWBBBBB/.../..B.W... W g8 4
+//     WWWWWWWW/WWWBBBBB/.../..B.W... W a1 2 misere,parallel
+impl fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in 0..Board::BOARD_SIZE {
+            if row > 0 { write!(f, "/")?; }
+            for col in 0..Board::BOARD_SIZE {
+                match self.board.get_coord_square_at((row, col).into()).1 {
+                    BoardSquare::Played(piece) => write!(f, "{}", piece)?,
+                    _ => write!(f, ".")?,
+                }
+            }
+        }
+
+        write!(f, " {} {} {}", self.player, self.solution, self.margin)?;
+        if self.rules != Rules::standard() {
+            write!(f, " {}", self.rules.to_string().replace(' ', ","))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        let (rows, player, solution, margin, rules) = match fields.as_slice() {
+            [rows, player, solution, margin] => (rows, player, solution, margin, None),
+            [rows, player, solution, margin, rules] => (rows, player, solution, margin, Some(rules)),
+            _ => return Err(String::from("expected a board, a player, a solution, a margin and optionally the rules")),
+        };
+
+        let rows: Vec<&str> = rows.split('/').collect();
+        if rows.len() != Board::BOARD_SIZE || rows.iter().any(|row| row.chars().count() != Board::BOARD_SIZE) {
+            return Err(String::from("board must have 8 rows of 8 squares"));
+        }
+
+        let mut board = Board::empty();
+        for (row, squares) in rows.iter().enumerate() {
+            for (col, square) in squares.chars().enumerate() {
+                match square {
+                    'B' => board.set_squares(&vec![(row, col).into()], Piece::Black),
+                    'W' => board.set_squares(&vec![(row, col).into()], Piece::White),
+                    '.' => (),
+                    _ => return Err(String::from("board squares must be B, W or .")),
+                }
+            }
+        }
+
+        let player = match *player {
+            "B" => Piece::Black,
+            "W" => Piece::White,
+            _ => return Err(String::from("player must be B or W")),
+        };
+        let solution: Coord = solution.parse().map_err(|err| format!("{}: {}", solution, err))?;
+        let margin = margin.parse().map_err(|_| String::from("margin must be a number"))?;
+        let rules = match rules {
+            Some(rules) => rules.replace(',', " ").parse().map_err(|err| format!("{}", err))?,
+            None => Rules::standard(),
+        };
+
+        let puzzle = Puzzle { board, player, solution, margin, rules };
+        if !is_valid_move(&puzzle.game(), solution) {
+            return Err(format!("{} is not a valid move for {}", solution, player));
+        }
+        Ok(puzzle)
+    }
+}
+
+fn is_valid_move(game: &Game, coord: Coord) -> bool {
+    game.state() != GameState::GameOver && game.current_turn().player == game.initial_player() && game.current_turn().valid_moves.iter().any(|mv| *mv.coord() == coord)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PuzzleOptions {
+    pub engine: Engine, // plays the self-played games; it can be shallow, since the puzzles are solved exactly
+    pub min_empties: usize,
+    pub max_empties: usize, // solving gets slow quickly beyond a dozen or so
+    pub rules: Rules, // of the self-played games; puzzles found in a given game keep its rules
+}
+
+impl Default for PuzzleOptions {
+    fn default() -> Self {
+        PuzzleOptions { engine: Engine::new(2), min_empties: 4, max_empties: 12, rules: Rules::standard() }
+    }
+}
+
+// Looks for puzzles in the positions of a game, from its initial position on.
+// Every move of a position with the right number of empty squares is solved,
+// so this takes a while with many empty squares.
+pub fn find_puzzles(game: &Game, options: &PuzzleOptions) -> Vec<Puzzle> {
+    let mut position = game.restart();
+    let mut puzzles = Vec::new();
+
+    for played in game.history() {
+        let empties = position.board().count_squares(BoardSquare::Unplayed);
+        if (options.min_empties..=options.max_empties).contains(&empties) {
+            puzzles.extend(only_winning_move(&position, &options.engine));
+        }
+
+        position.try_play(*played).expect("game history contains only valid moves");
+    }
+
+    puzzles
+}
+
+fn only_winning_move(game: &Game, engine: &Engine) -> Option<Puzzle> {
+    let player = game.current_turn().player;
+    let moves = &game.current_turn().valid_moves;
+    if moves.len() < 2 { return None; }
+
+    let mut winning = None;
+    for mv in moves.iter() {
+        let mut child = game.clone();
+        child.try_play(*mv.coord()).expect("valid moves can be played");
+
+        let (_, discs) = engine.solve(&child);
+        let margin = if child.current_turn().player == player { discs } else { -discs };
+        if margin > 0 {
+            if winning.is_some() { return None; }
+            winning = Some((*mv.coord(), margin));
+        }
+    }
+
+    winning.map(|(solution, margin)| Puzzle { board: *game.board(), player, solution, margin, rules: game.rules() })
+}
+
+// Plays a game against itself with the options' engine and rules, after a
+// few random opening moves drawn from the seed
+pub fn self_play(seed: u64, options: &PuzzleOptions) -> Game {
+    let mut rng = Rng::new(seed);
+    let mut game = Game::with_rules(options.rules);
+
+    while game.state() != GameState::GameOver {
+        let coord = match game.history().len() < RANDOM_OPENING_MOVES {
+            true => *game.current_turn().valid_moves[rng.below(game.current_turn().valid_moves.len())].coord(),
+            false => options.engine.choose_move(&game, None).expect("a game that isn't over has a move"),
+        };
+        game.try_play(coord).expect("only valid moves are chosen");
+    }
+
+    game
+}
+
+// Finds puzzles in the given number of self-played games, leaving out
+// positions that were already found
+pub fn generate(games: usize, seed: u64, options: &PuzzleOptions) -> Vec<Puzzle> {
+    let mut seeds = Rng::new(seed);
+    let mut puzzles: Vec<Puzzle> = Vec::new();
+
+    for _ in 0..games {
+        for puzzle in find_puzzles(&self_play(seeds.next_u64(), options), options) {
+            if !contains(&puzzles, &puzzle) {
+                puzzles.push(puzzle);
+            }
+        }
+    }
+
+    puzzles
+}
+
+pub fn contains(puzzles: &[Puzzle], puzzle: &Puzzle) -> bool {
+    puzzles.iter().any(|other| other.board == puzzle.board && other.player == puzzle.player && other.rules == puzzle.rules)
+}
+
+// A puzzle file has one puzzle per line, and may have empty lines and
+// comments starting with #
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(path: impl AsRef<Path>) -> Result<Vec<Puzzle>, PuzzleError> {
+    parse(&fs::read_to_string(path).map_err(PuzzleError::Io)?)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(path: impl AsRef<Path>, puzzles: &[Puzzle]) -> Result<(), PuzzleError> {
+    fs::write(path, format(puzzles)).map_err(PuzzleError::Io)
+}
+
+pub fn parse(text: &str) -> Result<Vec<Puzzle>, PuzzleError> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim().starts_with('#'))
+        .map(|(index, line)| line.parse().map_err(|message| PuzzleError::Parse { line: index + 1, message }))
+        .collect()
+}
+
+pub fn format(puzzles: &[Puzzle]) -> String {
+    let mut text = String::from("# Reversi puzzles: board, player to move, only winning move, final disc difference, rules unless standard\n");
+    for puzzle in puzzles {
+        text.push_str(&puzzle.to_string());
+        text.push('\n');
+    }
+    text
+}
+
+#[derive(Debug)]
+pub enum PuzzleError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for PuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PuzzleError::Io(err) => write!(f, "Could not access file: {}", err),
+            PuzzleError::Parse { line, message } => write!(f, "Line {}: {}", line, message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_puzzles, parse, format, self_play, Puzzle, PuzzleError, PuzzleOptions};
+    use crate::reversi::board::Board;
+    use crate::reversi::engine::Engine;
+    use crate::reversi::game::{Game, PlayError};
+    use crate::reversi::piece::Piece;
+    use crate::reversi::rules::{Rules, Start};
+
+    // White wins by taking h8, which flips f8 and g8; a1 is the only other
+    // move, and leaves black to take h8
+    fn puzzle_game() -> Game {
+        let mut board = Board::empty();
+        board.set_squares(&vec![(7, 1).into(), (7, 2).into(), (7, 3).into(), (7, 4).into()], Piece::White);
+        board.set_squares(&vec![(7, 5).into(), (7, 6).into()], Piece::Black);
+        board.set_squares(&vec![(0, 1).into()], Piece::Black);
+        board.set_squares(&vec![(0, 2).into()], Piece::White);
+        Game::from_position(board, Piece::White)
+    }
+
+    #[test]
+    fn the_only_winning_move_makes_a_puzzle() {
+        let mut game = puzzle_game();
+        game.try_play("h8".parse().unwrap()).unwrap();
+
+        let options = PuzzleOptions { engine: Engine::new(1), min_empties: 0, max_empties: 64, ..PuzzleOptions::default() };
+        let puzzles = find_puzzles(&game, &options);
+
+        assert_eq!(puzzles.len(), 1);
+        assert_eq!(puzzles[0].solution, "h8".parse().unwrap());
+        assert!(puzzles[0].margin > 0);
+        assert!(puzzles[0].check("h8".parse().unwrap()).unwrap());
+        assert!(!puzzles[0].check("a1".parse().unwrap()).unwrap());
+        assert!(matches!(puzzles[0].check("d4".parse().unwrap()), Err(PlayError::InvalidMove)));
+    }
+
+    #[test]
+    fn puzzles_roundtrip_through_text() {
+        let game = puzzle_game();
+        let puzzle = Puzzle { board: *game.board(), player: Piece::White, solution: "h8".parse().unwrap(), margin: 5, rules: Rules::standard() };
+
+        let text = format(&[puzzle.clone(), puzzle.clone()]);
+
+        assert_eq!(parse(&text).unwrap(), vec![puzzle.clone(), puzzle]);
+    }
+
+    #[test]
+    fn malformed_puzzles_report_their_line() {
+        let game = puzzle_game();
+        let puzzle = Puzzle { board: *game.board(), player: Piece::White, solution: "d4".parse().unwrap(), margin: 5, rules: Rules::standard() };
+
+        assert!(matches!(parse(&format!("# comment\n\n{}\n", puzzle)), Err(PuzzleError::Parse { line: 3, .. })));
+        assert!(matches!(parse("......../ W h8 1"), Err(PuzzleError::Parse { line: 1, .. })));
+        assert!(matches!(parse(&format!("{} anti,upside-down", Puzzle { solution: "h8".parse().unwrap(), ..puzzle })), Err(PuzzleError::Parse { line: 1, .. })));
+    }
+
+    #[test]
+    fn puzzles_keep_their_rules() {
+        let game = puzzle_game();
+        let rules = Rules::misere().with_start(Start::Parallel);
+        let puzzle = Puzzle { board: *game.board(), player: Piece::White, solution: "a1".parse().unwrap(), margin: 2, rules };
+
+        assert!(puzzle.to_string().ends_with(" a1 2 misere,parallel"));
+        assert_eq!(parse(&format(&[puzzle.clone(), puzzle.clone()])).unwrap(), vec![puzzle.clone(), puzzle.clone()]);
+        assert_eq!(puzzle.game().rules(), rules);
+
+        let options = PuzzleOptions { engine: Engine::new(1), rules: Rules::misere(), ..PuzzleOptions::default() };
+        assert_eq!(self_play(7, &options).rules(), Rules::misere());
+    }
+}
@@ -0,0 +1,268 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crate::reversi::board::Board;
+use crate::reversi::coord::Coord;
+use crate::reversi::game::Game;
+use crate::reversi::piece::{BoardSquare, Piece};
+
+type Color = [u8; 3];
+
+const BOARD_COLOR: Color = [0x2e, 0x7d, 0x32];
+const LINE_COLOR: Color = [0x1b, 0x3d, 0x1c];
+const HIGHLIGHT_COLOR: Color = [0x9e, 0xb8, 0x3a];
+const BLACK_DISC_COLOR: Color = [0x11, 0x11, 0x11];
+const WHITE_DISC_COLOR: Color = [0xf5, 0xf5, 0xf5];
+
+// Squares are at least this many pixels, so discs stay recognizable
+const MIN_SQUARE_SIZE: u32 = 4;
+
+// Larger squares are refused, which keeps images to 4096 pixels a side (48 MB
+// of pixels) and well within the GIF limit of 65535
+pub const MAX_SQUARE_SIZE: u32 = 512;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RasterOptions {
+    pub square_size: u32,
+    pub frame_delay: Duration, // how long each move is shown in animations
+}
+
+impl Default for RasterOptions {
+    fn default() -> Self {
+        RasterOptions { square_size: 40, frame_delay: Duration::from_millis(800) }
+    }
+}
+
+#[derive(Debug)]
+pub enum RasterError {
+    Io(io::Error),
+    Png(png::EncodingError),
+    Gif(gif::EncodingError),
+    TooLarge, // the square size is above MAX_SQUARE_SIZE
+}
+
+impl fmt::Display for RasterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RasterError::Io(err) => write!(f, "Could not write the image: {}", err),
+            RasterError::Png(err) => write!(f, "Could not encode the PNG image: {}", err),
+            RasterError::Gif(err) => write!(f, "Could not encode the GIF animation: {}", err),
+            RasterError::TooLarge => write!(f, "The image is too large, squares can be at most {} pixels", MAX_SQUARE_SIZE),
+        }
+    }
+}
+
+impl From<io::Error> for RasterError {
+    fn from(err: io::Error) -> Self {
+        RasterError::Io(err)
+    }
+}
+
+impl From<png::EncodingError> for RasterError {
+    fn from(err: png::EncodingError) -> Self {
+        RasterError::Png(err)
+    }
+}
+
+impl From<gif::EncodingError> for RasterError {
+    fn from(err: gif::EncodingError) -> Self {
+        RasterError::Gif(err)
+    }
+}
+
+// A square RGB image of the board, one byte per channel
+struct Canvas {
+    size: u32,
+    square_size: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    // The size of the image's sides, checked before any drawing
+    fn size_for(options: &RasterOptions) -> Result<u32, RasterError> {
+        match options.square_size {
+            square_size if square_size > MAX_SQUARE_SIZE => Err(RasterError::TooLarge),
+            square_size => Ok(square_size.max(MIN_SQUARE_SIZE) * Board::BOARD_SIZE as u32),
+        }
+    }
+
+    // Draws on a canvas of a size returned by `size_for`
+    fn draw(board: &Board, highlights: &[Coord], size: u32) -> Canvas {
+        let square_size = size / Board::BOARD_SIZE as u32;
+        let side = size as usize;
+        let mut canvas = Canvas { size, square_size, pixels: vec![0; side * side * 3] };
+
+        for row in 0..Board::BOARD_SIZE {
+            for col in 0..Board::BOARD_SIZE {
+                let coord = Coord::from((row, col));
+                let background = if highlights.contains(&coord) { HIGHLIGHT_COLOR } else { BOARD_COLOR };
+                canvas.fill_square(coord, background);
+
+                if let (_, BoardSquare::Played(piece)) = board.get_coord_square_at(coord) {
+                    canvas.draw_disc(coord, piece);
+                }
+            }
+        }
+
+        canvas
+    }
+
+    fn blend(&mut self, x: u32, y: u32, color: Color, alpha: f64) {
+        let index = (y as usize * self.size as usize + x as usize) * 3;
+        for (channel, value) in color.iter().enumerate() {
+            let current = self.pixels[index + channel] as f64;
+            self.pixels[index + channel] = (current + (*value as f64 - current) * alpha).round() as u8;
+        }
+    }
+
+    // Squares are drawn with a one pixel grid line on their top and left sides,
+    // and the last row and column get one on the outer side too
+    fn fill_square(&mut self, coord: Coord, color: Color) {
+        let (left, top) = (coord.col as u32 * self.square_size, coord.row as u32 * self.square_size);
+        let last = Board::BOARD_SIZE - 1;
+
+        for y in top..top + self.square_size {
+            for x in left..left + self.square_size {
+                let on_line = x == left || y == top
+                    || (coord.col == last && x == self.size - 1)
+                    || (coord.row == last && y == self.size - 1);
+                self.blend(x, y, if on_line { LINE_COLOR } else { color }, 1.0);
+            }
+        }
+    }
+
+    // Discs are antialiased by how far each pixel center is from the edge,
+    // with a dark outline so white discs stand out on highlighted squares
+    fn draw_disc(&mut self, coord: Coord, piece: Piece) {
+        let center_x = (coord.col as f64 + 0.5) * self.square_size as f64;
+        let center_y = (coord.row as f64 + 0.5) * self.square_size as f64;
+        let radius = self.square_size as f64 * 0.4;
+        let fill = match piece {
+            Piece::Black => BLACK_DISC_COLOR,
+            Piece::White => WHITE_DISC_COLOR,
+        };
+        let (left, top) = (coord.col as u32 * self.square_size, coord.row as u32 * self.square_size);
+
+        for y in top..top + self.square_size {
+            for x in left..left + self.square_size {
+                let distance = ((x as f64 + 0.5 - center_x).powi(2) + (y as f64 + 0.5 - center_y).powi(2)).sqrt();
+                let outline = (radius + 0.5 - distance).clamp(0.0, 1.0);
+                let inside = (radius - 0.5 - distance).clamp(0.0, 1.0);
+                self.blend(x, y, BLACK_DISC_COLOR, outline);
+                self.blend(x, y, fill, inside);
+            }
+        }
+    }
+}
+
+pub fn board_to_png<W: Write>(board: &Board, options: &RasterOptions, writer: W) -> Result<(), RasterError> {
+    let canvas = Canvas::draw(board, &[], Canvas::size_for(options)?);
+
+    let mut encoder = png::Encoder::new(writer, canvas.size, canvas.size);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&canvas.pixels)?;
+
+    Ok(())
+}
+
+// Replays the game from its initial position. Every move gets two frames: the
+// new disc with the discs it captures highlighted, then the discs flipped. The
+// final position is held three times longer before the animation loops.
+pub fn game_to_gif<W: Write>(game: &Game, options: &RasterOptions, writer: W) -> Result<(), RasterError> {
+    let size = Canvas::size_for(options)?;
+    let mut replay = game.restart();
+    let mut frames = vec![(Canvas::draw(replay.board(), &[], size), options.frame_delay)];
+
+    for coord in game.history() {
+        let player = replay.current_turn().player;
+        let mut placed = *replay.board();
+        placed.set_squares(&vec![*coord], player);
+
+        // The history was recorded by playing the moves, so they can be replayed
+        replay.try_play(*coord).expect("game history contains only valid moves");
+        let mut captured = replay.last_outcome().map(|outcome| outcome.changed_coords().clone()).unwrap_or_default();
+        captured.push(*coord);
+
+        frames.push((Canvas::draw(&placed, &captured, size), options.frame_delay / 2));
+        frames.push((Canvas::draw(replay.board(), &[*coord], size), options.frame_delay));
+    }
+
+    if let Some((_, delay)) = frames.last_mut() {
+        *delay *= 3;
+    }
+
+    let size = u16::try_from(size).map_err(|_| RasterError::TooLarge)?;
+    let mut encoder = gif::Encoder::new(writer, size, size, &[])?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+
+    for (canvas, delay) in frames.iter() {
+        let mut frame = gif::Frame::from_rgb_speed(size, size, &canvas.pixels, 10);
+        frame.delay = (delay.as_millis() / 10).min(u16::MAX as u128) as u16; // in hundredths of a second
+        encoder.write_frame(&frame)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{board_to_png, game_to_gif, RasterError, RasterOptions, BLACK_DISC_COLOR, BOARD_COLOR, MAX_SQUARE_SIZE, WHITE_DISC_COLOR};
+    use crate::reversi::board::Board;
+    use crate::reversi::game::Game;
+
+    #[test]
+    fn boards_are_encoded_as_png() {
+        let options = RasterOptions { square_size: 20, ..RasterOptions::default() };
+        let mut bytes = Vec::new();
+
+        board_to_png(&Board::new(), &options, &mut bytes).unwrap();
+
+        let mut reader = png::Decoder::new(bytes.as_slice()).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        let pixel = |x: usize, y: usize| [pixels[(y * 160 + x) * 3], pixels[(y * 160 + x) * 3 + 1], pixels[(y * 160 + x) * 3 + 2]];
+
+        assert_eq!((info.width, info.height), (160, 160));
+        assert_eq!(pixel(10, 10), BOARD_COLOR);
+        assert_eq!(pixel(70, 70), BLACK_DISC_COLOR); // d4
+        assert_eq!(pixel(90, 70), WHITE_DISC_COLOR); // e4
+    }
+
+    #[test]
+    fn games_are_animated_move_by_move() {
+        let mut game = Game::new();
+        for coord in ["d3", "c5", "f6"] {
+            assert!(game.try_play(coord.parse().unwrap()).is_ok());
+        }
+        let options = RasterOptions { square_size: 10, frame_delay: Duration::from_millis(500) };
+        let mut bytes = Vec::new();
+
+        game_to_gif(&game, &options, &mut bytes).unwrap();
+
+        let mut decoder = gif::DecodeOptions::new().read_info(bytes.as_slice()).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (80, 80));
+
+        let mut delays = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            delays.push(frame.delay);
+        }
+
+        assert_eq!(delays, vec![50, 25, 50, 25, 50, 25, 150]); // the initial position, then two frames per move
+    }
+
+    #[test]
+    fn oversized_squares_are_refused_before_drawing() {
+        let options = RasterOptions { square_size: u32::MAX, ..RasterOptions::default() };
+
+        assert!(matches!(board_to_png(&Board::new(), &options, Vec::new()), Err(RasterError::TooLarge)));
+        assert!(matches!(game_to_gif(&Game::new(), &options, Vec::new()), Err(RasterError::TooLarge)));
+
+        let just_too_large = RasterOptions { square_size: MAX_SQUARE_SIZE + 1, ..RasterOptions::default() };
+        assert!(matches!(board_to_png(&Board::new(), &just_too_large, Vec::new()), Err(RasterError::TooLarge)));
+    }
+}
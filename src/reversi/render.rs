@@ -0,0 +1,193 @@
+use std::fmt;
+
+use crate::reversi::board::Board;
+use crate::reversi::coord::Coord;
+use crate::reversi::game::{Game, GameState};
+use crate::reversi::piece::{BoardSquare, Piece};
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_WHITE_PIECE: &str = "\x1b[1;97m";
+const ANSI_BLACK_PIECE: &str = "\x1b[1;31m";
+const ANSI_LEGAL_MOVE: &str = "\x1b[32m";
+const ANSI_HIGHLIGHT: &str = "\x1b[43m";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RenderOptions {
+    pub legal_moves: bool, // mark the squares where the player to move can play
+    pub flip_counts: bool, // show how many discs each legal move flips instead of a plain mark
+    pub last_move: bool,   // mark the last move, like [B]
+    pub flipped: bool,     // mark the discs flipped by the last move, like (B)
+    pub stable: bool,      // mark the discs that can't be flipped anymore, like {B}
+    pub color: bool,       // colorize with ANSI escape codes
+}
+
+impl RenderOptions {
+    pub fn all() -> RenderOptions {
+        RenderOptions { legal_moves: true, flip_counts: true, last_move: true, flipped: true, stable: true, color: true }
+    }
+}
+
+// Draws a game's board in the same layout as `Display for Board`, with the
+// overlays chosen in the options
+pub struct BoardRenderer<'a> {
+    game: &'a Game,
+    options: RenderOptions,
+    stable: Vec<Coord>,
+}
+
+impl BoardRenderer<'_> {
+    pub fn new(game: &Game, options: RenderOptions) -> BoardRenderer<'_> {
+        let stable = match options.stable {
+            true => [Piece::White, Piece::Black].iter().flat_map(|piece| game.board().stable_discs(*piece)).collect(),
+            false => Vec::new(),
+        };
+
+        BoardRenderer { game, options, stable }
+    }
+
+    // The three characters inside a square, and the ANSI code to draw them with
+    fn square_contents(&self, coord: Coord) -> (String, Option<&'static str>) {
+        let (_, square) = self.game.board().get_coord_square_at(coord);
+        let last_outcome = self.game.last_outcome();
+
+        match square {
+            BoardSquare::Played(piece) => {
+                let color = match piece {
+                    Piece::White => ANSI_WHITE_PIECE,
+                    Piece::Black => ANSI_BLACK_PIECE,
+                };
+
+                if self.options.last_move && last_outcome.is_some_and(|outcome| *outcome.coord() == coord) {
+                    (format!("[{}]", piece), Some(color))
+                } else if self.options.flipped && last_outcome.is_some_and(|outcome| outcome.changed_coords().contains(&coord)) {
+                    (format!("({})", piece), Some(color))
+                } else if self.stable.contains(&coord) {
+                    (format!("{{{}}}", piece), Some(color))
+                } else {
+                    (format!(" {} ", piece), Some(color))
+                }
+            }
+            _ if self.options.legal_moves && self.game.state() != GameState::GameOver => {
+                let legal_move = self.game.current_turn().valid_moves.iter().find(|mv| *mv.coord() == coord);
+                match legal_move {
+                    Some(mv) if self.options.flip_counts => (format!("{:^3}", mv.changed_coords().len()), Some(ANSI_LEGAL_MOVE)),
+                    Some(_) => (String::from(" . "), Some(ANSI_LEGAL_MOVE)),
+                    None => (String::from("   "), None),
+                }
+            }
+            _ => (String::from("   "), None),
+        }
+    }
+}
+
+impl fmt::Display for BoardRenderer<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", Board::HEADER)?;
+        writeln!(f, "{}", Board::ROW_DIVIDER)?;
+
+        for row in 0..Board::BOARD_SIZE {
+            write!(f, " {} |", row + 1)?;
+
+            for col in 0..Board::BOARD_SIZE {
+                let coord = Coord::from((row, col));
+                let (contents, color) = self.square_contents(coord);
+                let highlighted = self.options.last_move && self.game.last_outcome().is_some_and(|outcome| *outcome.coord() == coord);
+
+                match color {
+                    Some(color) if self.options.color => {
+                        let background = if highlighted { ANSI_HIGHLIGHT } else { "" };
+                        write!(f, "{}{}{}{}|", color, background, contents, ANSI_RESET)?
+                    }
+                    _ => write!(f, "{}|", contents)?,
+                }
+            }
+
+            writeln!(f)?;
+            writeln!(f, "{}", Board::ROW_DIVIDER)?;
+        }
+
+        Ok(()) // if you got here, it means there were no errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BoardRenderer, RenderOptions};
+    use crate::reversi::board::Board;
+    use crate::reversi::game::Game;
+    use crate::reversi::piece::Piece;
+
+    fn row(rendered: &str, row: usize) -> &str {
+        rendered.lines().nth(2 * row + 2).unwrap()
+    }
+
+    #[test]
+    fn plain_rendering_matches_board_display() {
+        let game = Game::new();
+
+        assert_eq!(BoardRenderer::new(&game, RenderOptions::default()).to_string(), game.board().to_string());
+    }
+
+    #[test]
+    fn legal_moves_are_marked() {
+        let game = Game::new();
+        let options = RenderOptions { legal_moves: true, ..RenderOptions::default() };
+
+        let rendered = BoardRenderer::new(&game, options).to_string();
+
+        assert_eq!(row(&rendered, 2), " 3 |   |   |   | . |   |   |   |   |");
+        assert_eq!(row(&rendered, 3), " 4 |   |   | . | B | W |   |   |   |");
+    }
+
+    #[test]
+    fn legal_moves_show_how_many_discs_they_flip() {
+        let mut game = Game::new();
+        for coord in ["d3", "c3"] {
+            assert!(game.try_play(coord.parse().unwrap()).is_ok());
+        }
+        let options = RenderOptions { legal_moves: true, flip_counts: true, ..RenderOptions::default() };
+
+        let rendered = BoardRenderer::new(&game, options).to_string();
+
+        // White can play b3 flipping c3, or c4 flipping d4
+        assert_eq!(row(&rendered, 2), " 3 |   | 1 | B | W |   |   |   |   |");
+        assert_eq!(row(&rendered, 3), " 4 |   |   | 1 | B | W |   |   |   |");
+    }
+
+    #[test]
+    fn last_move_and_flipped_discs_are_marked() {
+        let mut game = Game::new();
+        assert!(game.try_play("d3".parse().unwrap()).is_ok());
+        let options = RenderOptions { last_move: true, flipped: true, ..RenderOptions::default() };
+
+        let rendered = BoardRenderer::new(&game, options).to_string();
+
+        assert_eq!(row(&rendered, 2), " 3 |   |   |   |[W]|   |   |   |   |");
+        assert_eq!(row(&rendered, 3), " 4 |   |   |   |(W)| W |   |   |   |");
+    }
+
+    #[test]
+    fn stable_discs_are_marked() {
+        let mut board = Board::new();
+        board.set_squares(&vec![(0, 0).into(), (0, 1).into()], Piece::White);
+        board.set_squares(&vec![(0, 2).into()], Piece::Black);
+        let game = Game::from_position(board, Piece::Black);
+        let options = RenderOptions { stable: true, ..RenderOptions::default() };
+
+        let rendered = BoardRenderer::new(&game, options).to_string();
+
+        assert_eq!(row(&rendered, 0), " 1 |{W}|{W}| B |   |   |   |   |   |");
+        assert_eq!(row(&rendered, 3), " 4 |   |   |   | B | W |   |   |   |");
+    }
+
+    #[test]
+    fn colors_are_only_added_when_asked() {
+        let game = Game::new();
+
+        let plain = BoardRenderer::new(&game, RenderOptions { color: false, ..RenderOptions::all() }).to_string();
+        let colored = BoardRenderer::new(&game, RenderOptions::all()).to_string();
+
+        assert!(!plain.contains('\x1b'));
+        assert!(colored.contains("\x1b[1;31m B \x1b[0m|"));
+    }
+}
@@ -0,0 +1,271 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::reversi::board::Board;
+use crate::reversi::coord::Coord;
+use crate::reversi::game::Game;
+use crate::reversi::piece::{BoardSquare, Piece};
+use crate::reversi::random::Rng;
+
+// The four squares in the middle of the board
+const CENTER: [(usize, usize); 4] = [(3, 3), (3, 4), (4, 3), (4, 4)];
+
+// Eight-move openings, from the crossed start, that the engine scores as
+// close to even after searching six moves ahead, in the spirit of the XOT
+// list. Each one leads to a different position, symmetries aside, with white
+// to move.
+const XOT_OPENINGS: [&str; 24] = [
+    "e6 d6 c4 f6 f7 f4 g7 b4",
+    "f5 d6 c6 f6 d7 c7 b7 d8",
+    "d3 c3 f5 d2 c2 f4 b2 g6",
+    "f5 f6 d3 f4 g7 d6 g6 d2",
+    "c4 c3 e6 b4 b3 d6 b2 f3",
+    "d3 c3 f5 f6 e6 d2 g7 g6",
+    "c4 c3 e6 b4 b2 f4 c5 e7",
+    "e6 f6 c4 e7 f7 f8 g6 g5",
+    "e6 f6 d3 c5 d6 c7 g7 d2",
+    "d3 c3 c4 e3 f6 d6 b2 b4",
+    "f5 d6 c6 b6 b7 f4 d3 c3",
+    "f5 f4 d3 d6 f6 f7 g7 c5",
+    "e6 f6 f5 f4 g7 d6 e3 f2",
+    "c4 e3 f2 e2 f3 g3 e6 f1",
+    "c4 c3 f5 f4 b2 g6 g5 g4",
+    "c4 c3 d3 c5 e6 d2 b2 f5",
+    "e6 f4 f3 f6 d3 f2 g7 d6",
+    "e6 f6 d3 e7 g7 e3 d6 c7",
+    "e6 f4 f3 f2 g2 d6 f5 g5",
+    "d3 c3 e6 f4 b2 e7 f6 c6",
+    "f5 d6 c6 b6 d3 d2 b7 f4",
+    "c4 e3 f5 c6 d6 g6 b7 b4",
+    "d3 c3 c4 e3 f3 g3 g2 d6",
+    "d3 c3 f5 d6 b2 f4 e6 d2",
+];
+
+// Which discs count once the game is over
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WinCondition {
+    MostDiscs,
+    FewestDiscs, // misère, or Anti-Reversi
+}
+
+// How the game starts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Start {
+    Crossed,  // the usual start, with each color on a diagonal of the center
+    Parallel, // each color on a column of the center, black on d and white on e
+    Original, // an empty board, where the first four moves fill the center squares in any order without flipping
+    Xot(usize), // the position after one of the balanced openings, by its index
+}
+
+impl Start {
+    pub fn xot_count() -> usize {
+        XOT_OPENINGS.len()
+    }
+
+    // One of the balanced openings, picked at random
+    pub fn random_xot(rng: &mut Rng) -> Start {
+        Start::Xot(rng.below(XOT_OPENINGS.len()))
+    }
+
+    // The starting board and the player to move on it
+    pub fn position(&self) -> (Board, Piece) {
+        match self {
+            Start::Crossed => (Board::new(), Piece::White),
+            Start::Parallel => {
+                let mut board = Board::empty();
+                board.set_squares(&vec![(3, 3).into(), (4, 3).into()], Piece::Black);
+                board.set_squares(&vec![(3, 4).into(), (4, 4).into()], Piece::White);
+                (board, Piece::White)
+            }
+            Start::Original => (Board::empty(), Piece::White),
+            Start::Xot(index) => {
+                let moves: Vec<Coord> = XOT_OPENINGS[*index].split_whitespace().map(|text| text.parse().expect("openings are in move notation")).collect();
+                let game = Game::replay(Board::new(), Piece::White, &moves).expect("openings are made of valid moves");
+                (*game.board(), game.current_turn().player)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Start {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Start::Crossed => write!(f, "crossed"),
+            Start::Parallel => write!(f, "parallel"),
+            Start::Original => write!(f, "original"),
+            Start::Xot(index) => write!(f, "xot-{}", index),
+        }
+    }
+}
+
+impl FromStr for Start {
+    type Err = ParseRulesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "crossed" => Ok(Start::Crossed),
+            "parallel" => Ok(Start::Parallel),
+            "original" => Ok(Start::Original),
+            text => match text.strip_prefix("xot-").map(str::parse::<usize>) {
+                Some(Ok(index)) if index < XOT_OPENINGS.len() => Ok(Start::Xot(index)),
+                _ => Err(ParseRulesError),
+            },
+        }
+    }
+}
+
+// Variations on the standard rules, chosen when a game is created. Moves
+// flip discs the same way in every variant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rules {
+    pub win_condition: WinCondition,
+    pub start: Start,
+}
+
+impl Rules {
+    pub fn standard() -> Rules {
+        Rules { win_condition: WinCondition::MostDiscs, start: Start::Crossed }
+    }
+
+    pub fn misere() -> Rules {
+        Rules { win_condition: WinCondition::FewestDiscs, ..Rules::standard() }
+    }
+
+    pub fn with_start(self, start: Start) -> Rules {
+        Rules { start, ..self }
+    }
+
+    // The squares that can be played without flipping anything: the empty
+    // center squares, with the original start, until all four are filled
+    pub fn free_squares(&self, board: &Board) -> Vec<Coord> {
+        match self.start {
+            Start::Original => CENTER.iter()
+                .map(|square| Coord::from(*square))
+                .filter(|coord| board.get_coord_square_at(*coord).1 == BoardSquare::Unplayed)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    // The player's disc difference as it counts towards winning, so it's
+    // positive when the player is ahead under these rules
+    pub fn margin(&self, board: &Board, player: Piece) -> i32 {
+        let difference = board.count_pieces(player) as i32 - board.count_pieces(player.opponent()) as i32;
+        match self.win_condition {
+            WinCondition::MostDiscs => difference,
+            WinCondition::FewestDiscs => -difference,
+        }
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules::standard()
+    }
+}
+
+// Rules are written as words, like "misere parallel", leaving out the usual
+// win condition and start, or as "standard" when they're both usual
+impl fmt::Display for Rules {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.win_condition, self.start) {
+            (WinCondition::MostDiscs, Start::Crossed) => write!(f, "standard"),
+            (WinCondition::MostDiscs, start) => write!(f, "{}", start),
+            (WinCondition::FewestDiscs, Start::Crossed) => write!(f, "misere"),
+            (WinCondition::FewestDiscs, start) => write!(f, "misere {}", start),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseRulesError;
+
+impl fmt::Display for ParseRulesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Rules must be `standard` or `misere`, optionally with a start: `crossed`, `parallel`, `original` or `xot-<n>` (n below {})",
+            XOT_OPENINGS.len()
+        )
+    }
+}
+
+impl FromStr for Rules {
+    type Err = ParseRulesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rules = Rules::standard();
+
+        for word in s.split_whitespace() {
+            match word {
+                "standard" => (),
+                "misere" | "misère" | "anti" => rules.win_condition = WinCondition::FewestDiscs,
+                start => rules.start = start.parse()?,
+            }
+        }
+
+        Ok(rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Rules, Start};
+    use crate::reversi::board::Board;
+    use crate::reversi::game::Game;
+    use crate::reversi::piece::{BoardSquare, Piece};
+
+    #[test]
+    fn misere_counts_discs_the_other_way() {
+        let mut board = Board::new();
+        board.set_squares(&vec![(2, 3).into(), (3, 3).into()], Piece::White); // d3 and d4, leaving black with e5
+
+        assert_eq!(Rules::standard().margin(&board, Piece::White), 3);
+        assert_eq!(Rules::misere().margin(&board, Piece::White), -3);
+        assert_eq!(Rules::misere().margin(&board, Piece::Black), 3);
+    }
+
+    #[test]
+    fn rules_roundtrip_through_text() {
+        for rules in [Rules::standard(), Rules::misere(), Rules::standard().with_start(Start::Parallel), Rules::misere().with_start(Start::Xot(3))] {
+            assert_eq!(rules.to_string().parse(), Ok(rules));
+        }
+        assert_eq!("misere parallel".parse(), Ok(Rules::misere().with_start(Start::Parallel)));
+        assert!("chess".parse::<Rules>().is_err());
+        assert!(format!("xot-{}", Start::xot_count()).parse::<Rules>().is_err());
+    }
+
+    #[test]
+    fn the_original_start_fills_the_center_first() {
+        let mut game = Game::with_rules(Rules::standard().with_start(Start::Original));
+        assert_eq!(game.current_turn().valid_moves.len(), 4);
+
+        for (index, square) in ["e4", "d4", "d5", "e5"].iter().enumerate() {
+            assert_eq!(game.current_turn().valid_moves.len(), 4 - index);
+            assert!(game.current_turn().valid_moves.iter().all(|mv| mv.changed_coords().is_empty()));
+            game.try_play(square.parse().unwrap()).unwrap();
+        }
+
+        // The center is now crossed, like the usual start, and play goes on as usual
+        assert_eq!(game.board(), &Board::new());
+        assert_eq!(game.current_turn().player, Piece::White);
+        assert_eq!(game.current_turn().valid_moves.len(), 4);
+    }
+
+    #[test]
+    fn every_xot_opening_starts_a_game() {
+        for index in 0..Start::xot_count() {
+            let game = Game::with_rules(Rules::standard().with_start(Start::Xot(index)));
+
+            assert_eq!(game.board().count_squares(BoardSquare::Unplayed), 64 - 12); // the four center discs and eight moves
+            assert_eq!(game.current_turn().player, Piece::White);
+            assert!(!game.current_turn().valid_moves.is_empty());
+        }
+
+        let parallel = Game::with_rules(Rules::standard().with_start(Start::Parallel));
+        assert_eq!(parallel.current_turn().valid_moves.len(), 4);
+    }
+}
@@ -0,0 +1,433 @@
+use std::fmt;
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs;
+use std::io;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::reversi::board::Board;
+use crate::reversi::clock::{self, Clock, Clocks, ParseTimeControlError, TimeControl};
+use crate::reversi::coord::Coord;
+use crate::reversi::engine::Engine;
+use crate::reversi::game::{Game, GameResult, PlayError};
+use crate::reversi::piece::{BoardSquare, Piece};
+use crate::reversi::rules::{ParseRulesError, Rules};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Player {
+    pub name: String,
+    pub engine: Option<Engine>, // None for human players
+}
+
+impl Player {
+    pub fn human(name: &str) -> Player {
+        Player { name: name.to_string(), engine: None }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Players {
+    pub white: Player,
+    pub black: Player,
+}
+
+impl Players {
+    pub fn get(&self, piece: Piece) -> &Player {
+        match piece {
+            Piece::White => &self.white,
+            Piece::Black => &self.black,
+        }
+    }
+
+    pub fn get_mut(&mut self, piece: Piece) -> &mut Player {
+        match piece {
+            Piece::White => &mut self.white,
+            Piece::Black => &mut self.black,
+        }
+    }
+}
+
+impl Default for Players {
+    fn default() -> Self {
+        Players { white: Player::human("White"), black: Player::human("Black") }
+    }
+}
+
+// A saved game keeps only what's needed to rebuild the Game exactly: the
+// initial position, who moves first and the moves played since. Everything
+// else (captures, passes, state) is recalculated by replaying the moves.
+// Engines are saved with their depth, and clocks with the time left and the
+// byo-yomi periods left, if any.
+//
+// The file format is plain text so it can be read and edited by hand:
+//
+//     # Reversi saved game
+//     version: 1
+//     white: Alice
+//     black: Computer
+//     black_engine: 4
+//     clock: fischer 300+5
+//     white_clock: 287.5 0
+//     black_clock: 296.1 0
+//     rules: misere
+//     first: W
+//     board:
+//     ........
+//     ........
+//     ........
+//     ...BW...
+//     ...WB...
+//     ........
+//     ........
+//     ........
+//     moves: d3 c3
+//
+// A game lost on time also has a `timeout: W` (or B) line with the loser.
+// The rules line is left out for standard games.
+#[derive(Clone)]
+pub struct SavedGame {
+    pub players: Players,
+    pub game: Game,
+    pub clocks: Option<Clocks>,
+}
+
+impl SavedGame {
+    const HEADER: &'static str = "# Reversi saved game";
+    const VERSION: u32 = 1;
+
+    pub fn new(players: Players, game: Game, clocks: Option<Clocks>) -> SavedGame {
+        SavedGame { players, game, clocks }
+    }
+
+    // Files aren't available in WebAssembly, where games are saved as text
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SaveError> {
+        fs::write(path, self.to_string()).map_err(SaveError::Io)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<Path>) -> Result<SavedGame, SaveError> {
+        fs::read_to_string(path).map_err(SaveError::Io)?.parse()
+    }
+
+    fn color_key(piece: Piece) -> &'static str {
+        match piece {
+            Piece::White => "white",
+            Piece::Black => "black",
+        }
+    }
+
+    fn parse_piece(line: usize, value: &str) -> Result<Piece, SaveError> {
+        match value {
+            "B" => Ok(Piece::Black),
+            "W" => Ok(Piece::White),
+            _ => Err(SaveError::parse(line, "player must be B or W")),
+        }
+    }
+
+    fn parse_engine(line: usize, value: &str) -> Result<Engine, SaveError> {
+        match value.parse() {
+            Ok(depth) if depth > 0 => Ok(Engine::new(depth)),
+            _ => Err(SaveError::parse(line, "engine depth must be a positive number")),
+        }
+    }
+
+    fn parse_clock(line: usize, value: &str) -> Result<(Duration, u32), SaveError> {
+        let error = || SaveError::parse(line, "clock must be the seconds left and the periods left");
+
+        match value.split_whitespace().collect::<Vec<&str>>().as_slice() {
+            [seconds, periods] => Ok((
+                clock::parse_seconds(seconds).ok_or_else(error)?,
+                periods.parse().map_err(|_| error())?,
+            )),
+            _ => Err(error()),
+        }
+    }
+
+    fn parse_board_row(board: &mut Board, line: usize, row: usize, text: &str) -> Result<(), SaveError> {
+        let squares: Vec<char> = text.trim().chars().collect();
+        if squares.len() != Board::BOARD_SIZE {
+            return Err(SaveError::parse(line, "board rows must have 8 squares"));
+        }
+
+        for (col, square) in squares.into_iter().enumerate() {
+            match square {
+                'B' => board.set_squares(&vec![(row, col).into()], Piece::Black),
+                'W' => board.set_squares(&vec![(row, col).into()], Piece::White),
+                '.' => (),
+                _ => return Err(SaveError::parse(line, "board squares must be B, W or .")),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for SavedGame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", SavedGame::HEADER)?;
+        writeln!(f, "version: {}", SavedGame::VERSION)?;
+        for piece in [Piece::White, Piece::Black].iter() {
+            let player = self.players.get(*piece);
+            writeln!(f, "{}: {}", SavedGame::color_key(*piece), player.name)?;
+            if let Some(engine) = player.engine {
+                writeln!(f, "{}_engine: {}", SavedGame::color_key(*piece), engine.depth())?;
+            }
+        }
+
+        if let Some(clocks) = &self.clocks {
+            writeln!(f, "clock: {}", clocks.get(Piece::White).control())?;
+            for piece in [Piece::White, Piece::Black].iter() {
+                let clock = clocks.get(*piece);
+                writeln!(f, "{}_clock: {} {}", SavedGame::color_key(*piece), clock::format_seconds(clock.remaining()), clock.periods_left())?;
+            }
+        }
+
+        if self.game.rules() != Rules::standard() {
+            writeln!(f, "rules: {}", self.game.rules())?;
+        }
+        writeln!(f, "first: {}", self.game.initial_player())?;
+
+        writeln!(f, "board:")?;
+        for row in 0..Board::BOARD_SIZE {
+            for col in 0..Board::BOARD_SIZE {
+                match self.game.initial_board().get_coord_square_at((row, col).into()) {
+                    (_, BoardSquare::Played(piece)) => write!(f, "{}", piece)?,
+                    _ => write!(f, ".")?,
+                }
+            }
+            writeln!(f)?;
+        }
+
+        let moves: Vec<String> = self.game.history().iter().map(|coord| coord.to_string()).collect();
+        writeln!(f, "moves: {}", moves.join(" "))?;
+
+        if let Some(GameResult::WinOnTime(winner)) = self.game.result() {
+            writeln!(f, "timeout: {}", winner.opponent())?;
+        }
+
+        Ok(()) // if you got here, it means there were no errors
+    }
+}
+
+impl FromStr for SavedGame {
+    type Err = SaveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut players = Players::default();
+        let mut first: Option<Piece> = None;
+        let mut board: Option<Board> = None;
+        let mut moves: Vec<Coord> = Vec::new();
+        let mut control: Option<TimeControl> = None;
+        let mut clock_times: [Option<(Duration, u32)>; 2] = [None, None];
+        let mut timeout: Option<(usize, Piece)> = None;
+        let mut rules = Rules::default();
+
+        let mut lines = s.lines().enumerate().map(|(i, text)| (i + 1, text));
+        while let Some((line, text)) = lines.next() {
+            let text = text.trim();
+            if text.is_empty() || text.starts_with('#') { continue; }
+
+            let (key, value) = match text.find(':') {
+                Some(index) => (text[..index].trim(), text[index + 1..].trim()),
+                None => return Err(SaveError::parse(line, "expected a line like `key: value`")),
+            };
+
+            match key {
+                "version" if value == SavedGame::VERSION.to_string() => (),
+                "version" => return Err(SaveError::parse(line, "unsupported version")),
+                "white" => players.white.name = value.to_string(),
+                "black" => players.black.name = value.to_string(),
+                "white_engine" => players.white.engine = Some(SavedGame::parse_engine(line, value)?),
+                "black_engine" => players.black.engine = Some(SavedGame::parse_engine(line, value)?),
+                "clock" => control = Some(value.parse().map_err(|err: ParseTimeControlError| SaveError::parse(line, &err.to_string()))?),
+                "white_clock" => clock_times[0] = Some(SavedGame::parse_clock(line, value)?),
+                "black_clock" => clock_times[1] = Some(SavedGame::parse_clock(line, value)?),
+                "timeout" => timeout = Some((line, SavedGame::parse_piece(line, value)?)),
+                "rules" => rules = value.parse().map_err(|err: ParseRulesError| SaveError::parse(line, &err.to_string()))?,
+                "first" => first = Some(SavedGame::parse_piece(line, value)?),
+                "board" => {
+                    let mut initial_board = Board::empty();
+                    for row in 0..Board::BOARD_SIZE {
+                        let (row_line, row_text) = lines.next().ok_or_else(|| SaveError::parse(line, "board is missing rows"))?;
+                        SavedGame::parse_board_row(&mut initial_board, row_line, row, row_text)?;
+                    }
+                    board = Some(initial_board);
+                }
+                "moves" => {
+                    moves = value
+                        .split_whitespace()
+                        .map(|token| token.parse().map_err(|err| SaveError::parse(line, &format!("{}: {}", token, err))))
+                        .collect::<Result<_, _>>()?;
+                }
+                _ => return Err(SaveError::parse(line, &format!("unknown key `{}`", key))),
+            }
+        }
+
+        let mut game = Game::from_position_with_rules(board.unwrap_or_default(), first.unwrap_or(Piece::White), rules);
+        for (index, coord) in moves.iter().enumerate() {
+            game.try_play(*coord).map_err(|error| SaveError::IllegalMove { index, coord: *coord, error })?;
+        }
+
+        if let Some((line, player)) = timeout {
+            game.lose_on_time(player).map_err(|_| SaveError::parse(line, "game was over before the timeout"))?;
+        }
+
+        // Clocks without saved times start from scratch
+        let clocks = control.map(|control| {
+            let clock = |times: Option<(Duration, u32)>| match times {
+                Some((remaining, periods_left)) => Clock::with_remaining(control, remaining, periods_left),
+                None => Clock::new(control),
+            };
+            Clocks::from_clocks(clock(clock_times[0]), clock(clock_times[1]))
+        });
+
+        Ok(SavedGame { players, game, clocks })
+    }
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+    IllegalMove { index: usize, coord: Coord, error: PlayError },
+}
+
+impl SaveError {
+    fn parse(line: usize, message: &str) -> SaveError {
+        SaveError::Parse { line, message: message.to_string() }
+    }
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "Could not access file: {}", err),
+            SaveError::Parse { line, message } => write!(f, "Line {}: {}", line, message),
+            SaveError::IllegalMove { index, coord, error } => write!(f, "Move {} ({}): {}", index + 1, coord, error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Player, Players, SaveError, SavedGame};
+    use crate::reversi::board::Board;
+    use crate::reversi::clock::{Clocks, TimeControl};
+    use crate::reversi::engine::Engine;
+    use crate::reversi::game::{Game, GameResult, GameState};
+    use crate::reversi::piece::Piece;
+    use crate::reversi::rules::Rules;
+
+    fn played_game() -> Game {
+        let mut game = Game::new();
+        for coord in ["d3", "c3", "c4", "e3"] {
+            assert!(game.try_play(coord.parse().unwrap()).is_ok());
+        }
+        game
+    }
+
+    #[test]
+    fn saved_game_roundtrips_through_text() {
+        let players = Players {
+            white: Player::human("Alice"),
+            black: Player { name: String::from("Computer"), engine: Some(Engine::new(3)) },
+        };
+        let saved = SavedGame::new(players.clone(), played_game(), None);
+
+        let text = saved.to_string();
+        let loaded: SavedGame = text.parse().unwrap();
+
+        assert_eq!(loaded.players, players);
+        assert_eq!(loaded.game.history(), saved.game.history());
+        assert_eq!(loaded.game.board(), saved.game.board());
+        assert_eq!(loaded.game.state(), saved.game.state());
+        assert_eq!(loaded.game.current_turn().player, saved.game.current_turn().player);
+        assert_eq!(loaded.to_string(), text);
+    }
+
+    #[test]
+    fn saved_game_keeps_custom_initial_position() {
+        let mut board = Board::new();
+        board.set_squares(&vec![(0, 0).into()], Piece::Black);
+        let saved = SavedGame::new(Players::default(), Game::from_position(board, Piece::Black), None);
+
+        let loaded: SavedGame = saved.to_string().parse().unwrap();
+
+        assert_eq!(loaded.game.initial_board(), &board);
+        assert_eq!(loaded.game.initial_player(), Piece::Black);
+        assert_eq!(loaded.game.state(), GameState::New);
+    }
+
+    #[test]
+    fn saved_game_can_be_written_and_read_from_disk() {
+        let path = std::env::temp_dir().join(format!("reversi-save-test-{}.txt", std::process::id()));
+        let saved = SavedGame::new(Players::default(), played_game(), None);
+
+        saved.save(&path).unwrap();
+        let loaded = SavedGame::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.game.history(), saved.game.history());
+    }
+
+    #[test]
+    fn saved_game_keeps_rules() {
+        let saved = SavedGame::new(Players::default(), Game::with_rules(Rules::misere()), None);
+
+        let text = saved.to_string();
+        let loaded: SavedGame = text.parse().unwrap();
+
+        assert!(text.contains("rules: misere"));
+        assert_eq!(loaded.game.rules(), Rules::misere());
+        assert!(!SavedGame::new(Players::default(), Game::new(), None).to_string().contains("rules"));
+    }
+
+    #[test]
+    fn saved_game_keeps_clocks() {
+        let mut clocks = Clocks::new(TimeControl::ByoYomi {
+            main: Duration::from_secs(60),
+            period: Duration::from_secs(10),
+            periods: 3,
+        });
+        clocks.get_mut(Piece::White).spend(Duration::from_millis(75_500));
+        clocks.get_mut(Piece::Black).spend(Duration::from_millis(1_250));
+        let saved = SavedGame::new(Players::default(), played_game(), Some(clocks));
+
+        let loaded: SavedGame = saved.to_string().parse().unwrap();
+
+        assert_eq!(loaded.clocks, Some(clocks));
+    }
+
+    #[test]
+    fn saved_game_keeps_loss_on_time() {
+        let mut game = played_game();
+        game.lose_on_time(Piece::Black).unwrap();
+        let saved = SavedGame::new(Players::default(), game, None);
+
+        let loaded: SavedGame = saved.to_string().parse().unwrap();
+
+        assert_eq!(loaded.game.result(), Some(GameResult::WinOnTime(Piece::White)));
+    }
+
+    #[test]
+    fn loading_illegal_move_fails() {
+        let text = "version: 1\nmoves: d3 a1\n";
+
+        assert!(matches!(text.parse::<SavedGame>(), Err(SaveError::IllegalMove { index: 1, .. })));
+    }
+
+    #[test]
+    fn loading_malformed_file_reports_line() {
+        assert!(matches!("version: 1\nboard:\n........\n".parse::<SavedGame>(), Err(SaveError::Parse { line: 2, .. })));
+        assert!(matches!("version: 1\ncolor: red\n".parse::<SavedGame>(), Err(SaveError::Parse { line: 2, .. })));
+        assert!(matches!("version: 2\n".parse::<SavedGame>(), Err(SaveError::Parse { line: 1, .. })));
+        assert!(matches!("moves: d3 zz\n".parse::<SavedGame>(), Err(SaveError::Parse { line: 1, .. })));
+        assert!(matches!("clock: blitz\n".parse::<SavedGame>(), Err(SaveError::Parse { line: 1, .. })));
+        assert!(matches!("white_engine: 0\n".parse::<SavedGame>(), Err(SaveError::Parse { line: 1, .. })));
+    }
+}
@@ -0,0 +1,199 @@
+use std::fmt::Write;
+
+use crate::reversi::board::Board;
+use crate::reversi::coord::Coord;
+use crate::reversi::game::Game;
+use crate::reversi::piece::{BoardSquare, Piece};
+
+const BOARD_COLOR: &str = "#2e7d32";
+const LINE_COLOR: &str = "#1b3d1c";
+const BLACK_DISC_COLOR: &str = "#111111";
+const WHITE_DISC_COLOR: &str = "#f5f5f5";
+const HIGHLIGHT_COLOR: &str = "#ffeb3b";
+const ARROW_COLOR: &str = "#d32f2f";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgOptions {
+    pub square_size: u32,
+    pub coordinates: bool,   // draw a1..h8 labels around the board
+    pub move_numbers: bool,  // number discs with the move that placed them, for games only
+    pub highlights: Vec<Coord>,
+    pub arrows: Vec<(Coord, Coord)>,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions { square_size: 40, coordinates: true, move_numbers: false, highlights: Vec::new(), arrows: Vec::new() }
+    }
+}
+
+pub fn board_to_svg(board: &Board, options: &SvgOptions) -> String {
+    render(board, &[], options)
+}
+
+// Move numbers come from the game's history, so discs of the initial position
+// are never numbered
+pub fn game_to_svg(game: &Game, options: &SvgOptions) -> String {
+    let move_numbers: Vec<(Coord, usize)> = match options.move_numbers {
+        true => game.history().iter().enumerate().map(|(i, coord)| (*coord, i + 1)).collect(),
+        false => Vec::new(),
+    };
+
+    render(game.board(), &move_numbers, options)
+}
+
+fn render(board: &Board, move_numbers: &[(Coord, usize)], options: &SvgOptions) -> String {
+    let size = options.square_size as f64;
+    let margin = if options.coordinates { size / 2.0 } else { 0.0 };
+    let board_size = size * Board::BOARD_SIZE as f64;
+    let total = board_size + 2.0 * margin;
+    let center = |index: usize| margin + (index as f64 + 0.5) * size;
+
+    // Writing to a String can't fail, so the results of write! are ignored
+    let mut svg = String::new();
+    let _ = writeln!(svg, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}" viewBox="0 0 {0} {0}">"#, total);
+
+    if !options.arrows.is_empty() {
+        let _ = writeln!(
+            svg,
+            r#"<defs><marker id="arrowhead" markerWidth="4" markerHeight="4" refX="2" refY="2" orient="auto"><path d="M0,0 L4,2 L0,4 z" fill="{}"/></marker></defs>"#,
+            ARROW_COLOR
+        );
+    }
+
+    let _ = writeln!(svg, r#"<rect x="0" y="0" width="{0}" height="{0}" fill="white"/>"#, total);
+    let _ = writeln!(svg, r#"<rect x="{0}" y="{0}" width="{1}" height="{1}" fill="{2}"/>"#, margin, board_size, BOARD_COLOR);
+
+    for coord in options.highlights.iter() {
+        let _ = writeln!(
+            svg,
+            r#"<rect class="highlight" x="{}" y="{}" width="{2}" height="{2}" fill="{3}" fill-opacity="0.5"/>"#,
+            margin + coord.col as f64 * size,
+            margin + coord.row as f64 * size,
+            size,
+            HIGHLIGHT_COLOR
+        );
+    }
+
+    for line in 0..=Board::BOARD_SIZE {
+        let offset = margin + line as f64 * size;
+        let _ = writeln!(svg, r#"<line x1="{0}" y1="{1}" x2="{0}" y2="{2}" stroke="{3}"/>"#, offset, margin, margin + board_size, LINE_COLOR);
+        let _ = writeln!(svg, r#"<line x1="{1}" y1="{0}" x2="{2}" y2="{0}" stroke="{3}"/>"#, offset, margin, margin + board_size, LINE_COLOR);
+    }
+
+    if options.coordinates {
+        let font_size = size * 0.35;
+        for index in 0..Board::BOARD_SIZE {
+            let _ = writeln!(
+                svg,
+                r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                center(index), margin / 2.0, font_size, (b'a' + index as u8) as char
+            );
+            let _ = writeln!(
+                svg,
+                r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                margin / 2.0, center(index), font_size, index + 1
+            );
+        }
+    }
+
+    for row in 0..Board::BOARD_SIZE {
+        for col in 0..Board::BOARD_SIZE {
+            let coord = Coord::from((row, col));
+            let piece = match board.get_coord_square_at(coord) {
+                (_, BoardSquare::Played(piece)) => piece,
+                _ => continue,
+            };
+            let (fill, text_color) = match piece {
+                Piece::Black => (BLACK_DISC_COLOR, WHITE_DISC_COLOR),
+                Piece::White => (WHITE_DISC_COLOR, BLACK_DISC_COLOR),
+            };
+
+            let _ = writeln!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="{}" stroke="{}"/>"#,
+                center(col), center(row), size * 0.4, fill, BLACK_DISC_COLOR
+            );
+
+            // A square can be played only once, so the last number is the right one
+            if let Some((_, number)) = move_numbers.iter().rev().find(|(played, _)| *played == coord) {
+                let _ = writeln!(
+                    svg,
+                    r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" fill="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                    center(col), center(row), size * 0.4, text_color, number
+                );
+            }
+        }
+    }
+
+    for (from, to) in options.arrows.iter() {
+        let _ = writeln!(
+            svg,
+            r#"<line class="arrow" x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}" stroke-opacity="0.8" marker-end="url(#arrowhead)"/>"#,
+            center(from.col), center(from.row), center(to.col), center(to.row), ARROW_COLOR, size / 10.0
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{board_to_svg, game_to_svg, SvgOptions};
+    use crate::reversi::board::Board;
+    use crate::reversi::game::Game;
+
+    #[test]
+    fn board_is_drawn_with_its_discs() {
+        let svg = board_to_svg(&Board::new(), &SvgOptions::default());
+
+        assert!(svg.starts_with("<?xml"));
+        assert!(svg.contains(r#"width="360" height="360""#)); // 8 squares of 40, plus margins for coordinates
+        assert_eq!(svg.matches("<circle").count(), 4);
+        assert_eq!(svg.matches("<line").count(), 18);
+        assert!(svg.contains(">a</text>") && svg.contains(">8</text>"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn coordinates_can_be_left_out() {
+        let options = SvgOptions { coordinates: false, square_size: 10, ..SvgOptions::default() };
+
+        let svg = board_to_svg(&Board::new(), &options);
+
+        assert!(svg.contains(r#"width="80" height="80""#));
+        assert!(!svg.contains("<text"));
+    }
+
+    #[test]
+    fn game_discs_are_numbered_by_move() {
+        let mut game = Game::new();
+        for coord in ["d3", "c5", "f6"] {
+            assert!(game.try_play(coord.parse().unwrap()).is_ok());
+        }
+        let options = SvgOptions { coordinates: false, move_numbers: true, ..SvgOptions::default() };
+
+        let svg = game_to_svg(&game, &options);
+
+        assert_eq!(svg.matches("<circle").count(), 7);
+        assert_eq!(svg.matches("<text").count(), 3);
+        assert!(svg.contains(">1</text>") && svg.contains(">2</text>") && svg.contains(">3</text>"));
+    }
+
+    #[test]
+    fn highlights_and_arrows_are_drawn() {
+        let options = SvgOptions {
+            highlights: vec!["d3".parse().unwrap(), "e6".parse().unwrap()],
+            arrows: vec![("d3".parse().unwrap(), "d5".parse().unwrap())],
+            ..SvgOptions::default()
+        };
+
+        let svg = board_to_svg(&Board::new(), &options);
+
+        assert_eq!(svg.matches(r#"class="highlight""#).count(), 2);
+        assert_eq!(svg.matches(r#"class="arrow""#).count(), 1);
+        assert!(svg.contains(r#"<marker id="arrowhead""#));
+    }
+}
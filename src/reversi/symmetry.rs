@@ -0,0 +1,163 @@
+use std::cmp::Ordering;
+
+use crate::reversi::board::Board;
+use crate::reversi::coord::Coord;
+use crate::reversi::piece::{BoardSquare, Piece};
+
+const LAST: usize = Board::BOARD_SIZE - 1;
+
+// The eight ways of turning or reflecting the board that keep positions
+// equivalent. The rules are symmetric, so a move sequence played on a
+// transformed board leads to the transformed position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    Identity,
+    Rotate90,         // a quarter turn, taking a1 to h1
+    Rotate180,
+    Rotate270,        // three quarter turns, taking a1 to a8
+    FlipHorizontal,   // mirrors the columns, taking a1 to h1
+    FlipVertical,     // mirrors the rows, taking a1 to a8
+    FlipDiagonal,     // across the a1-h8 diagonal, taking b1 to a2
+    FlipAntiDiagonal, // across the h1-a8 diagonal, taking a1 to h8
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+        Symmetry::FlipHorizontal,
+        Symmetry::FlipVertical,
+        Symmetry::FlipDiagonal,
+        Symmetry::FlipAntiDiagonal,
+    ];
+
+    // The symmetry that undoes this one
+    pub fn inverse(&self) -> Symmetry {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            other => *other,
+        }
+    }
+
+    pub fn coord(&self, coord: Coord) -> Coord {
+        let Coord { row, col } = coord;
+        let (row, col) = match self {
+            Symmetry::Identity => (row, col),
+            Symmetry::Rotate90 => (col, LAST - row),
+            Symmetry::Rotate180 => (LAST - row, LAST - col),
+            Symmetry::Rotate270 => (LAST - col, row),
+            Symmetry::FlipHorizontal => (row, LAST - col),
+            Symmetry::FlipVertical => (LAST - row, col),
+            Symmetry::FlipDiagonal => (col, row),
+            Symmetry::FlipAntiDiagonal => (LAST - col, LAST - row),
+        };
+
+        Coord { row, col }
+    }
+
+    pub fn board(&self, board: &Board) -> Board {
+        let mut transformed = Board::empty();
+
+        for coord in Board::coords() {
+            if let (_, BoardSquare::Played(piece)) = board.get_coord_square_at(coord) {
+                transformed.set_squares(&vec![self.coord(coord)], piece);
+            }
+        }
+
+        transformed
+    }
+
+    pub fn moves(&self, moves: &[Coord]) -> Vec<Coord> {
+        moves.iter().map(|coord| self.coord(*coord)).collect()
+    }
+}
+
+// Orders boards square by square from a1, with empty squares first, then
+// black discs, then white ones
+fn compare_boards(a: &Board, b: &Board) -> Ordering {
+    let key = |square: BoardSquare| match square {
+        BoardSquare::Played(Piece::Black) => 1,
+        BoardSquare::Played(Piece::White) => 2,
+        _ => 0,
+    };
+
+    Board::coords()
+        .map(|coord| key(a.get_coord_square_at(coord).1).cmp(&key(b.get_coord_square_at(coord).1)))
+        .find(|ordering| *ordering != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
+
+// The smallest of a board's symmetric forms, which is the same for all of
+// them, along with the symmetry that leads to it from the given board. Moves
+// found for the canonical board are mapped back with the symmetry's inverse.
+pub fn canonical(board: &Board) -> (Board, Symmetry) {
+    let mut best = (*board, Symmetry::Identity);
+
+    for symmetry in Symmetry::ALL.iter().skip(1) {
+        let transformed = symmetry.board(board);
+        if compare_boards(&transformed, &best.0) == Ordering::Less {
+            best = (transformed, *symmetry);
+        }
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{canonical, Symmetry};
+    use crate::reversi::board::Board;
+    use crate::reversi::coord::Coord;
+    use crate::reversi::game::Game;
+    use crate::reversi::piece::Piece;
+
+    fn coord(text: &str) -> Coord {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn symmetries_are_undone_by_their_inverse() {
+        for symmetry in Symmetry::ALL.iter() {
+            for square in Board::coords() {
+                assert_eq!(symmetry.inverse().coord(symmetry.coord(square)), square);
+            }
+        }
+
+        assert_eq!(Symmetry::Rotate90.coord(coord("a1")), coord("h1"));
+        assert_eq!(Symmetry::FlipAntiDiagonal.coord(coord("a1")), coord("h8"));
+        assert_eq!(Symmetry::FlipDiagonal.coord(coord("b1")), coord("a2"));
+    }
+
+    #[test]
+    fn transformed_moves_lead_to_transformed_positions() {
+        let moves = [coord("d3"), coord("c5"), coord("f6"), coord("f5"), coord("e6")];
+        let game = Game::replay(Board::new(), Piece::White, &moves).unwrap();
+
+        for symmetry in Symmetry::ALL.iter() {
+            let start = symmetry.board(&Board::new());
+            let transformed = Game::replay(start, Piece::White, &symmetry.moves(&moves)).unwrap();
+
+            assert_eq!(*transformed.board(), symmetry.board(game.board()));
+        }
+    }
+
+    #[test]
+    fn symmetric_positions_share_a_canonical_form() {
+        let game = Game::replay(Board::new(), Piece::White, &[coord("d3"), coord("c5")]).unwrap();
+        let (canonical_board, symmetry) = canonical(game.board());
+
+        for other in Symmetry::ALL.iter() {
+            assert_eq!(canonical(&other.board(game.board())).0, canonical_board);
+        }
+        assert_eq!(symmetry.board(game.board()), canonical_board);
+
+        // A move on the canonical board maps back to the same move on the original
+        let canonical_game = Game::from_position(canonical_board, Piece::White);
+        let canonical_move = *canonical_game.current_turn().valid_moves[0].coord();
+        let original_move = symmetry.inverse().coord(canonical_move);
+        assert!(game.current_turn().valid_moves.iter().any(|mv| *mv.coord() == original_move));
+    }
+}
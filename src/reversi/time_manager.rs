@@ -0,0 +1,96 @@
+use std::time::{Duration, Instant};
+
+use crate::reversi::clock::Clock;
+
+// Decides how long the engine may think about a move. The soft limit is the
+// time it aims to use, so no new search iteration is started after it; the hard
+// limit aborts the search even in the middle of an iteration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeManager {
+    soft: Duration,
+    hard: Duration,
+}
+
+impl TimeManager {
+    const HARD_TO_SOFT_RATIO: u32 = 4;
+
+    pub fn new(soft: Duration, hard: Duration) -> TimeManager {
+        TimeManager { soft: soft.min(hard), hard }
+    }
+
+    // Spreads the remaining time over the moves the player is still expected to
+    // make, which is about half of the empty squares, plus any time that can
+    // be used for free on this move, like an increment
+    pub fn for_move(clock: &Clock, empties: usize) -> TimeManager {
+        let moves_to_go = (empties as u32 / 2).max(1);
+        let mut hard = clock.time_for_move() / 2;
+
+        // In byo-yomi overtime, going over the period would lose it
+        if clock.remaining().is_zero() {
+            hard = hard.min(clock.free_time_for_move() * 9 / 10);
+        }
+
+        let soft = (clock.remaining() / moves_to_go + clock.free_time_for_move()) * 9 / 10;
+        TimeManager::new(soft.min(hard), (soft * TimeManager::HARD_TO_SOFT_RATIO).min(hard))
+    }
+
+    pub fn soft_limit(&self) -> Duration {
+        self.soft
+    }
+
+    pub fn hard_limit(&self) -> Duration {
+        self.hard
+    }
+
+    pub fn soft_deadline(&self, start: Instant) -> Instant {
+        start + self.soft
+    }
+
+    pub fn hard_deadline(&self, start: Instant) -> Instant {
+        start + self.hard
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::TimeManager;
+    use crate::reversi::clock::{Clock, TimeControl};
+
+    fn secs(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn time_is_spread_over_remaining_moves() {
+        // 60 empty squares, so about 30 moves to go
+        let clock = Clock::new(TimeControl::SuddenDeath { total: secs(300) });
+        let manager = TimeManager::for_move(&clock, 60);
+
+        assert_eq!(manager.soft_limit(), secs(9));
+        assert_eq!(manager.hard_limit(), secs(36));
+
+        // Near the end of the game, more time is given to each move but never
+        // more than half of what's left
+        let manager = TimeManager::for_move(&clock, 2);
+        assert_eq!(manager.hard_limit(), secs(150));
+        assert!(manager.soft_limit() <= manager.hard_limit());
+    }
+
+    #[test]
+    fn increment_is_added_to_the_budget() {
+        let clock = Clock::new(TimeControl::Fischer { total: secs(300), increment: secs(10) });
+
+        assert_eq!(TimeManager::for_move(&clock, 60).soft_limit(), secs(18));
+    }
+
+    #[test]
+    fn byo_yomi_overtime_stays_within_the_period() {
+        let clock = Clock::new(TimeControl::ByoYomi { main: Duration::ZERO, period: secs(10), periods: 3 });
+        let manager = TimeManager::for_move(&clock, 60);
+
+        assert_eq!(manager.soft_limit(), secs(9));
+        assert_eq!(manager.hard_limit(), secs(9));
+    }
+}
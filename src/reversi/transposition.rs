@@ -0,0 +1,139 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::reversi::board::Board;
+use crate::reversi::coord::Coord;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Bound {
+    Exact,
+    Lower, // the score is at least this much (the search failed high)
+    Upper, // the score is at most this much (the search failed low)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Entry {
+    pub depth: u32,
+    pub score: i32,
+    pub bound: Bound,
+    pub best_move: Option<Coord>,
+}
+
+impl Entry {
+    // Packs the entry in 64 bits: score (32), depth (8), bound (2) and best move (7)
+    fn pack(&self) -> u64 {
+        let best_move = match self.best_move {
+            Some(coord) => (coord.row * Board::BOARD_SIZE + coord.col) as u64,
+            None => 0x7f,
+        };
+        let bound = match self.bound {
+            Bound::Exact => 0,
+            Bound::Lower => 1,
+            Bound::Upper => 2,
+        };
+
+        (self.score as u32 as u64) | (self.depth.min(0xff) as u64) << 32 | bound << 40 | best_move << 42
+    }
+
+    fn unpack(data: u64) -> Entry {
+        let best_move = match (data >> 42 & 0x7f) as usize {
+            0x7f => None,
+            index => Some(Board::coord_at(index)),
+        };
+        let bound = match data >> 40 & 0x3 {
+            0 => Bound::Exact,
+            1 => Bound::Lower,
+            _ => Bound::Upper,
+        };
+
+        Entry { depth: (data >> 32 & 0xff) as u32, score: data as u32 as i32, bound, best_move }
+    }
+}
+
+// A fixed-size hash table of search results shared by all search threads
+// without locking. Each slot stores the key XORed with the data, so a slot
+// torn by two threads writing at once fails the key check instead of
+// returning a wrong entry.
+pub struct TranspositionTable {
+    slots: Vec<(AtomicU64, AtomicU64)>,
+}
+
+impl TranspositionTable {
+    pub const DEFAULT_SIZE: usize = 1 << 16;
+
+    // The size is rounded up to a power of two, so slots can be found by masking
+    pub fn new(size: usize) -> TranspositionTable {
+        let slots = (0..size.max(1).next_power_of_two()).map(|_| (AtomicU64::new(0), AtomicU64::new(0))).collect();
+        TranspositionTable { slots }
+    }
+
+    fn slot(&self, hash: u64) -> &(AtomicU64, AtomicU64) {
+        &self.slots[hash as usize & (self.slots.len() - 1)]
+    }
+
+    pub fn probe(&self, hash: u64) -> Option<Entry> {
+        let (key, data) = self.slot(hash);
+        let data = data.load(Ordering::Relaxed);
+
+        if data != 0 && key.load(Ordering::Relaxed) ^ data == hash {
+            Some(Entry::unpack(data))
+        } else {
+            None
+        }
+    }
+
+    // Entries are always replaced, except by shallower results for the same position
+    pub fn store(&self, hash: u64, entry: Entry) {
+        if let Some(existing) = self.probe(hash) {
+            if existing.depth > entry.depth { return; }
+        }
+
+        let (key, data) = self.slot(hash);
+        let packed = entry.pack();
+        key.store(hash ^ packed, Ordering::Relaxed);
+        data.store(packed, Ordering::Relaxed);
+    }
+
+    pub fn clear(&self) {
+        for (key, data) in self.slots.iter() {
+            key.store(0, Ordering::Relaxed);
+            data.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        TranspositionTable::new(TranspositionTable::DEFAULT_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bound, Entry, TranspositionTable};
+
+    #[test]
+    fn stored_entries_can_be_probed() {
+        let table = TranspositionTable::new(16);
+        let entry = Entry { depth: 5, score: -1234, bound: Bound::Lower, best_move: Some((7, 6).into()) };
+
+        table.store(0xdead_beef, entry);
+
+        assert_eq!(table.probe(0xdead_beef), Some(entry));
+        assert_eq!(table.probe(0xdead_beef + 16), None); // same slot, different position
+    }
+
+    #[test]
+    fn deeper_entries_are_not_replaced_by_shallower_ones() {
+        let table = TranspositionTable::new(16);
+        let deep = Entry { depth: 6, score: 10, bound: Bound::Exact, best_move: None };
+        let shallow = Entry { depth: 2, score: 99, bound: Bound::Upper, best_move: Some((0, 0).into()) };
+
+        table.store(42, deep);
+        table.store(42, shallow);
+
+        assert_eq!(table.probe(42), Some(deep));
+
+        table.clear();
+        assert_eq!(table.probe(42), None);
+    }
+}
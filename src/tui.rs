@@ -0,0 +1,353 @@
+use std::io::{self, Stdout, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton, MouseEventKind};
+use crossterm::style::{Attribute, Color, Print, ResetColor, SetAttribute, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+
+use crate::reversi::board::Board;
+use crate::reversi::clock::Clock;
+use crate::reversi::coord::Coord;
+use crate::reversi::driver::MatchDriver;
+use crate::reversi::game::{Game, GameResult, GameState};
+use crate::reversi::piece::{BoardSquare, Piece};
+
+// Screen position of the top left corner of the board grid
+const BOARD_LEFT: u16 = 2;
+const BOARD_TOP: u16 = 1;
+const PANEL_LEFT: u16 = BOARD_LEFT + 4 * Board::BOARD_SIZE as u16 + 5;
+
+const TICK: Duration = Duration::from_millis(100);
+const FLIP_FRAME: Duration = Duration::from_millis(150);
+const FLIP_FRAMES: u32 = 2;
+const MOVE_LIST_ROWS: usize = 10;
+
+const KEYS_HELP: &str = "arrows/hjkl: move  enter/space/click: play  q: quit";
+
+// Keeps the terminal in raw mode on the alternate screen while alive, and
+// restores it when dropped, even if the game panics
+struct TerminalGuard {
+    stdout: Stdout,
+}
+
+impl TerminalGuard {
+    fn new() -> io::Result<TerminalGuard> {
+        let mut stdout = io::stdout();
+        terminal::enable_raw_mode()?;
+        execute!(stdout, EnterAlternateScreen, EnableMouseCapture, Hide)?;
+        Ok(TerminalGuard { stdout })
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(self.stdout, Show, DisableMouseCapture, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+struct Tui {
+    driver: MatchDriver,
+    cursor: Coord,
+    message: String,
+    flip_started: Instant,
+}
+
+pub fn run(driver: MatchDriver) -> io::Result<()> {
+    let mut guard = TerminalGuard::new()?;
+    let mut tui = Tui {
+        driver,
+        cursor: (3, 3).into(),
+        message: String::new(),
+        flip_started: Instant::now(),
+    };
+
+    loop {
+        tui.driver.check_time();
+        tui.draw(&mut guard.stdout)?;
+
+        if tui.driver.game().state() != GameState::GameOver && tui.driver.current_player().engine.is_some() {
+            tui.message = format!("{} is thinking...", tui.driver.current_player().name);
+            tui.draw(&mut guard.stdout)?;
+            tui.play_engine_move();
+            continue;
+        }
+
+        if !event::poll(TICK)? { continue; }
+
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Up | KeyCode::Char('k') => tui.move_cursor(-1, 0),
+                KeyCode::Down | KeyCode::Char('j') => tui.move_cursor(1, 0),
+                KeyCode::Left | KeyCode::Char('h') => tui.move_cursor(0, -1),
+                KeyCode::Right | KeyCode::Char('l') => tui.move_cursor(0, 1),
+                KeyCode::Enter | KeyCode::Char(' ') => tui.play(tui.cursor),
+                _ => (),
+            },
+            Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(coord) = square_at(mouse.column, mouse.row) {
+                    tui.cursor = coord;
+                    tui.play(coord);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+impl Tui {
+    fn move_cursor(&mut self, rows: isize, cols: isize) {
+        self.cursor = move_cursor(self.cursor, rows, cols);
+    }
+
+    fn play(&mut self, coord: Coord) {
+        match self.driver.play(coord) {
+            Ok(()) => self.start_flip(String::new()),
+            Err(err) => self.message = err.to_string(),
+        }
+    }
+
+    fn play_engine_move(&mut self) {
+        let name = self.driver.current_player().name.clone();
+
+        match self.driver.play_engine_move() {
+            Some(Ok(coord)) => self.start_flip(format!("{} played {}", name, coord)),
+            Some(Err(err)) => self.message = err.to_string(),
+            None => (),
+        }
+    }
+
+    fn start_flip(&mut self, message: String) {
+        self.flip_started = Instant::now();
+        self.message = message;
+    }
+
+    // Flipped discs first keep their old color, then show as turning, then
+    // settle on their new color
+    fn flip_frame(&self) -> Option<u32> {
+        let frame = (self.flip_started.elapsed().as_millis() / FLIP_FRAME.as_millis()) as u32;
+        if frame < FLIP_FRAMES { Some(frame) } else { None }
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        queue!(out, Clear(ClearType::All))?;
+        self.draw_board(out)?;
+        self.draw_panel(out)?;
+
+        let status_row = BOARD_TOP + 2 * Board::BOARD_SIZE as u16 + 2;
+        queue!(out, MoveTo(0, status_row), Print(&self.message))?;
+        queue!(out, MoveTo(0, status_row + 1), SetForegroundColor(Color::DarkGrey), Print(KEYS_HELP), ResetColor)?;
+
+        out.flush()
+    }
+
+    fn draw_board(&self, out: &mut impl Write) -> io::Result<()> {
+        let border = format!("+{}", "---+".repeat(Board::BOARD_SIZE));
+
+        for col in 0..Board::BOARD_SIZE {
+            queue!(out, MoveTo(BOARD_LEFT + 2 + 4 * col as u16, BOARD_TOP - 1), Print((b'a' + col as u8) as char))?;
+        }
+
+        for row in 0..Board::BOARD_SIZE {
+            let y = BOARD_TOP + 2 * row as u16;
+            queue!(out, MoveTo(BOARD_LEFT, y), Print(&border))?;
+            queue!(out, MoveTo(0, y + 1), Print(row + 1), MoveTo(BOARD_LEFT, y + 1), Print("|"))?;
+
+            for col in 0..Board::BOARD_SIZE {
+                let coord = Coord::from((row, col));
+                queue!(out, MoveTo(BOARD_LEFT + 1 + 4 * col as u16, y + 1))?;
+                self.draw_square(out, coord)?;
+                queue!(out, Print("|"))?;
+            }
+        }
+        queue!(out, MoveTo(BOARD_LEFT, BOARD_TOP + 2 * Board::BOARD_SIZE as u16), Print(&border))?;
+
+        Ok(())
+    }
+
+    fn draw_square(&self, out: &mut impl Write, coord: Coord) -> io::Result<()> {
+        let game = self.driver.game();
+        let (_, square) = game.board().get_coord_square_at(coord);
+        let is_legal = game.state() != GameState::GameOver
+            && game.current_turn().valid_moves.iter().any(|mv| *mv.coord() == coord);
+        let is_last_move = game.last_outcome().is_some_and(|outcome| *outcome.coord() == coord);
+        let is_flipped = game.last_outcome().is_some_and(|outcome| outcome.changed_coords().contains(&coord));
+
+        let (glyph, color) = match (square, self.flip_frame()) {
+            (BoardSquare::Played(piece), Some(0)) if is_flipped => (piece.opponent().to_string(), piece_color(piece.opponent())),
+            (BoardSquare::Played(_), Some(_)) if is_flipped => (String::from("*"), Color::Yellow),
+            (BoardSquare::Played(piece), _) => (piece.to_string(), piece_color(piece)),
+            _ if is_legal => (String::from("·"), Color::Green),
+            _ => (String::from(" "), Color::Reset),
+        };
+
+        if coord == self.cursor { queue!(out, SetAttribute(Attribute::Reverse))?; }
+        if is_last_move { queue!(out, SetAttribute(Attribute::Underlined))?; }
+        queue!(out, SetForegroundColor(color), Print(format!(" {} ", glyph)), ResetColor, SetAttribute(Attribute::Reset))
+    }
+
+    fn draw_panel(&self, out: &mut impl Write) -> io::Result<()> {
+        let session = self.driver.session();
+        let game = &session.game;
+        let mut row = BOARD_TOP;
+
+        for piece in [Piece::White, Piece::Black].iter() {
+            let to_move = game.state() != GameState::GameOver && game.current_turn().player == *piece;
+            let clock = session.clocks.as_ref().map(|clocks| {
+                let clock = clocks.get(*piece);
+                let elapsed = if to_move { self.driver.elapsed() } else { Duration::ZERO };
+                Clock::with_remaining(clock.control(), clock.remaining().saturating_sub(elapsed), clock.periods_left()).to_string()
+            });
+
+            queue!(
+                out,
+                MoveTo(PANEL_LEFT, row),
+                SetForegroundColor(piece_color(*piece)),
+                Print(format!("{} {} ({})", if to_move { ">" } else { " " }, session.players.get(*piece).name, piece)),
+                ResetColor,
+                Print(format!(
+                    "  discs: {:2}  stable: {:2}  {}",
+                    game.board().count_pieces(*piece),
+                    game.board().stable_discs(*piece).len(),
+                    clock.unwrap_or_default()
+                )),
+            )?;
+            row += 1;
+        }
+
+        row += 1;
+        let result = match game.result() {
+            Some(GameResult::Win(winner)) => format!("Game over! {} wins", session.players.get(winner).name),
+            Some(GameResult::WinOnTime(winner)) => format!("Game over on time! {} wins", session.players.get(winner).name),
+            Some(GameResult::Draw) => String::from("Game over! It's a draw"),
+            None if game.state() == GameState::PlayedAndPassed => {
+                format!("{} had to pass", session.players.get(game.current_turn().player.opponent()).name)
+            }
+            None => String::new(),
+        };
+        queue!(out, MoveTo(PANEL_LEFT, row), Print(result))?;
+
+        row += 2;
+        queue!(out, MoveTo(PANEL_LEFT, row), SetAttribute(Attribute::Bold), Print("Moves"), SetAttribute(Attribute::Reset))?;
+        for line in move_list(game, MOVE_LIST_ROWS) {
+            row += 1;
+            queue!(out, MoveTo(PANEL_LEFT, row), Print(line))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn piece_color(piece: Piece) -> Color {
+    match piece {
+        Piece::White => Color::White,
+        Piece::Black => Color::Red,
+    }
+}
+
+// Moves the cursor, wrapping around the edges of the board
+fn move_cursor(cursor: Coord, rows: isize, cols: isize) -> Coord {
+    let size = Board::BOARD_SIZE as isize;
+    Coord {
+        row: (cursor.row as isize + rows).rem_euclid(size) as usize,
+        col: (cursor.col as isize + cols).rem_euclid(size) as usize,
+    }
+}
+
+// Finds the square under a screen position, if any; borders don't count
+fn square_at(x: u16, y: u16) -> Option<Coord> {
+    if x <= BOARD_LEFT || y <= BOARD_TOP { return None; }
+
+    let (dx, dy) = ((x - BOARD_LEFT - 1) as usize, (y - BOARD_TOP - 1) as usize);
+    if dx % 4 == 3 || dy % 2 == 1 { return None; }
+
+    let coord = Coord::from((dy / 2, dx / 4));
+    if coord.row < Board::BOARD_SIZE && coord.col < Board::BOARD_SIZE { Some(coord) } else { None }
+}
+
+// Numbered moves, two per line, the first player's then the other's, keeping
+// only the last lines that fit. The game is replayed to tell who played each
+// move, so a pass leaves its column as "--".
+fn move_list(game: &Game, max_lines: usize) -> Vec<String> {
+    let mut position = game.restart();
+    let mut columns: Vec<String> = Vec::new();
+
+    for coord in game.history() {
+        let column_player = match columns.len() % 2 {
+            0 => game.initial_player(),
+            _ => game.initial_player().opponent(),
+        };
+        if position.current_turn().player != column_player {
+            columns.push(String::from("--"));
+        }
+        columns.push(coord.to_string());
+        position.try_play(*coord).expect("game history contains only valid moves");
+    }
+
+    let lines: Vec<String> = columns
+        .chunks(2)
+        .enumerate()
+        .map(|(i, pair)| format!("{:2}. {}", i + 1, pair.join("  ")))
+        .collect();
+
+    lines[lines.len().saturating_sub(max_lines)..].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{move_cursor, move_list, square_at, BOARD_LEFT, BOARD_TOP};
+    use crate::reversi::board::Board;
+    use crate::reversi::coord::Coord;
+    use crate::reversi::game::Game;
+    use crate::reversi::piece::Piece;
+
+    #[test]
+    fn cursor_wraps_around_the_board() {
+        assert_eq!(move_cursor((0, 0).into(), -1, 0), Coord::from((7, 0)));
+        assert_eq!(move_cursor((7, 7).into(), 0, 1), Coord::from((7, 0)));
+        assert_eq!(move_cursor((3, 3).into(), 1, -1), Coord::from((4, 2)));
+    }
+
+    #[test]
+    fn mouse_positions_map_to_squares() {
+        assert_eq!(square_at(BOARD_LEFT + 1, BOARD_TOP + 1), Some(Coord::from((0, 0))));
+        assert_eq!(square_at(BOARD_LEFT + 3, BOARD_TOP + 1), Some(Coord::from((0, 0))));
+        assert_eq!(square_at(BOARD_LEFT + 5, BOARD_TOP + 3), Some(Coord::from((1, 1))));
+        assert_eq!(square_at(BOARD_LEFT + 31, BOARD_TOP + 15), Some(Coord::from((7, 7))));
+
+        assert_eq!(square_at(BOARD_LEFT, BOARD_TOP + 1), None); // left border
+        assert_eq!(square_at(BOARD_LEFT + 4, BOARD_TOP + 1), None); // border between squares
+        assert_eq!(square_at(BOARD_LEFT + 1, BOARD_TOP + 2), None); // border between rows
+        assert_eq!(square_at(BOARD_LEFT + 33, BOARD_TOP + 1), None); // right of the board
+        assert_eq!(square_at(BOARD_LEFT + 1, BOARD_TOP + 17), None); // below the board
+    }
+
+    #[test]
+    fn move_list_numbers_pairs_of_moves() {
+        let mut game = Game::new();
+        for coord in ["d3", "c5", "f6"] {
+            game.try_play(coord.parse().unwrap()).unwrap();
+        }
+
+        assert_eq!(move_list(&game, 10), vec![" 1. d3  c5", " 2. f6"]);
+        assert_eq!(move_list(&game, 1), vec![" 2. f6"]);
+        assert!(move_list(&Game::new(), 10).is_empty());
+    }
+
+    #[test]
+    fn move_list_marks_passes() {
+        // After white takes a1, black has no move and white plays again at f1
+        let mut board = Board::empty();
+        board.set_squares(&vec![(0, 1).into(), (0, 6).into()], Piece::Black);
+        board.set_squares(&vec![(0, 2).into(), (0, 7).into()], Piece::White);
+        let mut game = Game::from_position(board, Piece::White);
+        for coord in ["a1", "f1"] {
+            game.try_play(coord.parse().unwrap()).unwrap();
+        }
+
+        assert_eq!(move_list(&game, 10), vec![" 1. a1  --", " 2. f1"]);
+    }
+}
@@ -0,0 +1,135 @@
+use wasm_bindgen::prelude::*;
+
+use crate::reversi::board::Board;
+use crate::reversi::coord::Coord;
+use crate::reversi::engine::Engine;
+use crate::reversi::game::{Game, GameResult, GameState};
+use crate::reversi::piece::{BoardSquare, Piece};
+
+// A game for JavaScript, exported as `Game`. Coords are strings in the usual
+// notation (like "d3") and players are "W" or "B". Errors are thrown as strings.
+#[wasm_bindgen(js_name = Game)]
+pub struct WasmGame {
+    game: Game,
+}
+
+#[wasm_bindgen(js_class = Game)]
+impl WasmGame {
+    #[wasm_bindgen(constructor)]
+    #[allow(clippy::new_without_default)] // JavaScript has no use for Default
+    pub fn new() -> WasmGame {
+        WasmGame { game: Game::new() }
+    }
+
+    #[wasm_bindgen(js_name = legalMoves)]
+    pub fn legal_moves(&self) -> Vec<String> {
+        match self.game.state() {
+            GameState::GameOver => Vec::new(),
+            _ => self.game.current_turn().valid_moves.iter().map(|mv| mv.coord().to_string()).collect(),
+        }
+    }
+
+    pub fn play(&mut self, coord: &str) -> Result<(), String> {
+        let coord: Coord = coord.parse().map_err(|err| format!("{}", err))?;
+        self.game.try_play(coord).map_err(|err| err.to_string())
+    }
+
+    // Returns the move taken back, if there was one
+    pub fn undo(&mut self) -> Option<String> {
+        self.game.undo().map(|coord| coord.to_string())
+    }
+
+    // The 64 squares row by row, from a1 to h8: 0 for empty, 1 for white and 2 for black
+    pub fn board(&self) -> Vec<u8> {
+        let mut squares = Vec::with_capacity(Board::BOARD_SIZE * Board::BOARD_SIZE);
+        for row in 0..Board::BOARD_SIZE {
+            for col in 0..Board::BOARD_SIZE {
+                squares.push(match self.game.board().get_coord_square_at((row, col).into()) {
+                    (_, BoardSquare::Played(Piece::White)) => 1,
+                    (_, BoardSquare::Played(Piece::Black)) => 2,
+                    _ => 0,
+                });
+            }
+        }
+
+        squares
+    }
+
+    #[wasm_bindgen(js_name = currentPlayer)]
+    pub fn current_player(&self) -> String {
+        self.game.current_turn().player.to_string()
+    }
+
+    #[wasm_bindgen(js_name = whiteDiscs)]
+    pub fn white_discs(&self) -> usize {
+        self.game.board().count_pieces(Piece::White)
+    }
+
+    #[wasm_bindgen(js_name = blackDiscs)]
+    pub fn black_discs(&self) -> usize {
+        self.game.board().count_pieces(Piece::Black)
+    }
+
+    // True when the last move made the other player pass
+    pub fn passed(&self) -> bool {
+        self.game.state() == GameState::PlayedAndPassed
+    }
+
+    #[wasm_bindgen(js_name = isOver)]
+    pub fn is_over(&self) -> bool {
+        self.game.state() == GameState::GameOver
+    }
+
+    // The winner, "draw", or nothing while the game is still on
+    pub fn winner(&self) -> Option<String> {
+        match self.game.result()? {
+            GameResult::Win(winner) | GameResult::WinOnTime(winner) => Some(winner.to_string()),
+            GameResult::Draw => Some(String::from("draw")),
+        }
+    }
+
+    pub fn history(&self) -> Vec<String> {
+        self.game.history().iter().map(Coord::to_string).collect()
+    }
+
+    // The engine's choice for the current player, without playing it. Browsers
+    // run it on a single thread, so the search is limited by depth alone.
+    #[wasm_bindgen(js_name = engineMove)]
+    pub fn engine_move(&self, depth: u32) -> Option<String> {
+        Engine::new(depth.max(1)).choose_move(&self.game, None).map(|coord| coord.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WasmGame;
+
+    #[test]
+    fn games_are_played_through_strings() {
+        let mut game = WasmGame::new();
+        assert_eq!(game.legal_moves(), vec!["d3", "c4", "f5", "e6"]);
+        assert_eq!(game.current_player(), "W");
+
+        assert!(game.play("d3").is_ok());
+        assert_eq!(game.play("a1"), Err(String::from("Invalid move")));
+        assert!(game.play("zz").is_err());
+
+        assert_eq!(game.board()[2 * 8 + 3], 1); // d3
+        assert_eq!((game.white_discs(), game.black_discs()), (4, 1));
+        assert_eq!(game.history(), vec!["d3"]);
+        assert_eq!(game.winner(), None);
+
+        assert_eq!(game.undo(), Some(String::from("d3")));
+        assert_eq!(game.undo(), None);
+    }
+
+    #[test]
+    fn engine_suggests_a_legal_move() {
+        let game = WasmGame::new();
+
+        let suggestion = game.engine_move(2).unwrap();
+
+        assert!(game.legal_moves().contains(&suggestion));
+        assert!(game.history().is_empty());
+    }
+}
@@ -0,0 +1,61 @@
+/* Exercises the C API through include/reversi.h. Prints FAIL and exits with a
+ * non-zero status on the first check that doesn't hold. */
+
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "reversi.h"
+
+#define CHECK(condition)                                                           \
+    do {                                                                           \
+        if (!(condition)) {                                                        \
+            fprintf(stderr, "FAIL %s:%d: %s\n", __FILE__, __LINE__, #condition);   \
+            exit(1);                                                               \
+        }                                                                          \
+    } while (0)
+
+int main(void) {
+    ReversiGame *game = reversi_game_new();
+    CHECK(game != NULL);
+    CHECK(reversi_game_state(game) == REVERSI_GAME_STATE_NEW);
+    CHECK(reversi_game_current_player(game) == REVERSI_PIECE_WHITE);
+
+    /* White opens with d3, c4, f5 or e6 */
+    ReversiCoord moves[64];
+    size_t count = reversi_game_legal_moves(game, moves, 64);
+    CHECK(count == 4);
+    CHECK(moves[0].row == 2 && moves[0].col == 3);
+
+    CHECK(reversi_game_play(game, 2, 3) == REVERSI_STATUS_OK);
+    CHECK(reversi_game_play(game, 0, 0) == REVERSI_STATUS_INVALID_MOVE);
+    CHECK(reversi_game_play(game, 8, 8) == REVERSI_STATUS_OUT_OF_BOUNDS);
+    CHECK(reversi_game_play(NULL, 2, 3) == REVERSI_STATUS_NULL_POINTER);
+    CHECK(reversi_game_current_player(game) == REVERSI_PIECE_BLACK);
+    CHECK(reversi_game_count(game, REVERSI_PIECE_WHITE) == 4);
+    CHECK(reversi_game_count(game, REVERSI_PIECE_BLACK) == 1);
+    CHECK(reversi_game_count(game, 7) == 0);
+
+    ReversiSquare board[64];
+    CHECK(reversi_game_board(game, board) == REVERSI_STATUS_OK);
+    CHECK(board[2 * 8 + 3] == REVERSI_SQUARE_WHITE);
+    CHECK(board[0] == REVERSI_SQUARE_EMPTY);
+    CHECK(reversi_game_square(game, 9, 0) == REVERSI_SQUARE_OUT_OF_BOUNDS);
+
+    /* The engine suggests a move without playing it */
+    ReversiGame *copy = reversi_game_clone(game);
+    ReversiCoord best;
+    CHECK(reversi_engine_search(copy, 3, 2, &best) == REVERSI_STATUS_OK);
+    CHECK(reversi_game_play(copy, best.row, best.col) == REVERSI_STATUS_OK);
+    CHECK(reversi_game_current_player(game) == REVERSI_PIECE_BLACK);
+
+    CHECK(reversi_game_undo(game) == REVERSI_STATUS_OK);
+    CHECK(reversi_game_undo(game) == REVERSI_STATUS_NOTHING_TO_UNDO);
+    CHECK(reversi_game_square(game, 2, 3) == REVERSI_SQUARE_EMPTY);
+
+    reversi_game_free(copy);
+    reversi_game_free(game);
+    reversi_game_free(NULL);
+
+    printf("OK\n");
+    return 0;
+}
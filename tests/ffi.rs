@@ -0,0 +1,47 @@
+// Builds the library as a C shared library, then the C test program against
+// it, and runs it. The test is skipped when there's no C compiler.
+#![cfg(all(feature = "ffi", unix))]
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn c_program_uses_the_c_api() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let target_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("ffi");
+    let program = target_dir.join("ffi_test");
+
+    // The shared library is only built on request, in a target directory of
+    // its own so this doesn't wait for the build that runs the test
+    let built = Command::new(env!("CARGO"))
+        .args(["rustc", "--lib", "--no-default-features", "--features", "ffi", "--crate-type", "cdylib", "--target-dir"])
+        .arg(&target_dir)
+        .current_dir(&manifest_dir)
+        .status()
+        .unwrap();
+    assert!(built.success(), "the shared library doesn't build");
+    let library_dir = target_dir.join("debug");
+    let compiler = env::var("CC").unwrap_or_else(|_| String::from("cc"));
+
+    let compiled = Command::new(&compiler)
+        .arg(manifest_dir.join("tests/c/ffi_test.c"))
+        .arg("-I").arg(manifest_dir.join("include"))
+        .arg("-L").arg(&library_dir)
+        .arg(format!("-Wl,-rpath,{}", library_dir.display()))
+        .arg("-lreversi_rust")
+        .arg("-o").arg(&program)
+        .status();
+
+    match compiled {
+        Ok(status) => assert!(status.success(), "the C test program doesn't compile"),
+        Err(_) => return eprintln!("Skipping the C API test, {} isn't available", compiler),
+    }
+
+    // Other builds of the library may be on the search path cargo sets for tests
+    let inherited = env::var_os("LD_LIBRARY_PATH").unwrap_or_default();
+    let search_path = env::join_paths(std::iter::once(library_dir.clone()).chain(env::split_paths(&inherited))).unwrap();
+    let output = Command::new(&program).env("LD_LIBRARY_PATH", search_path).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "OK\n");
+}
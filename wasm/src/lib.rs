@@ -0,0 +1 @@
+pub use reversi_rust::wasm::*;